@@ -0,0 +1,16 @@
+use advent_of_code_2022::day13::decoder_key_from_args;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    match decoder_key_from_args(std::env::args().skip(1)) {
+        Ok((positions, key)) => {
+            println!("{:?}", positions);
+            println!("{:?}", key);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
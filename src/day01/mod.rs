@@ -1,3 +1,8 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+
 const INPUT: &str = include_str!("input.txt");
 
 #[derive(Debug)]
@@ -5,52 +10,314 @@ struct Elf {
     calories: Vec<usize>,
 }
 
+#[derive(Debug, PartialEq)]
+struct Summary {
+    count: usize,
+    total: usize,
+    min: usize,
+    max: usize,
+    mean: f64,
+    median: f64,
+}
+
 impl Elf {
     fn total_calories(&self) -> usize {
         self.calories.iter().sum()
     }
+
+    fn summary(&self) -> Option<Summary> {
+        let mut sorted = self.calories.clone();
+        sorted.sort_unstable();
+        let (&min, &max) = (sorted.first()?, sorted.last()?);
+        let count = sorted.len();
+        let total = self.total_calories();
+        let median = if count.is_multiple_of(2) {
+            (sorted[count / 2 - 1] + sorted[count / 2]) as f64 / 2.0
+        } else {
+            sorted[count / 2] as f64
+        };
+        Some(Summary {
+            count,
+            total,
+            min,
+            max,
+            mean: total as f64 / count as f64,
+            median,
+        })
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} items, total {}, min {}, max {}, mean {:.1}, median {:.1}",
+            self.count, self.total, self.min, self.max, self.mean, self.median
+        )
+    }
+}
+
+#[derive(Debug)]
+enum InventoryError {
+    Io(io::Error),
+    InvalidCalories { line: usize, text: String },
+}
+
+impl From<io::Error> for InventoryError {
+    fn from(e: io::Error) -> Self {
+        InventoryError::Io(e)
+    }
+}
+
+impl fmt::Display for InventoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InventoryError::Io(e) => write!(f, "{e}"),
+            InventoryError::InvalidCalories { line, text } => {
+                write!(f, "line {line}: invalid calories {text:?}")
+            }
+        }
+    }
+}
+
+// Reads one elf at a time, elves being separated by blank lines with either
+// LF or CRLF line endings.
+struct ElfReader<R: Read> {
+    reader: BufReader<R>,
+    line: usize,
+    buf: String,
+}
+
+impl<R: Read> ElfReader<R> {
+    fn new(reader: R) -> Self {
+        ElfReader {
+            reader: BufReader::new(reader),
+            line: 0,
+            buf: String::new(),
+        }
+    }
+
+    fn read_elf(&mut self) -> Result<Option<Elf>, InventoryError> {
+        let mut calories = vec![];
+        loop {
+            self.buf.clear();
+            if self.reader.read_line(&mut self.buf)? == 0 {
+                break;
+            }
+            self.line += 1;
+            let text = self.buf.trim_end_matches(['\r', '\n']);
+            if text.is_empty() {
+                if calories.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            calories.push(text.parse().map_err(|_| InventoryError::InvalidCalories {
+                line: self.line,
+                text: text.to_string(),
+            })?);
+        }
+        Ok((!calories.is_empty()).then_some(Elf { calories }))
+    }
+}
+
+impl<R: Read> Iterator for ElfReader<R> {
+    type Item = Result<Elf, InventoryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_elf().transpose()
+    }
 }
 
 fn parse(input: &str) -> Vec<Elf> {
-    input
-        .split("\r\n\r\n")
-        .map(|lines| Elf {
-            calories: lines
-                .lines()
-                .map(|line| line.parse().expect("a number"))
-                .collect(),
-        })
-        .collect()
+    ElfReader::new(input.as_bytes())
+        .collect::<Result<_, _>>()
+        .expect("a valid inventory")
+}
+
+// Keeps the `k` largest values pushed so far, without storing the others
+struct TopK {
+    k: usize,
+    heap: BinaryHeap<Reverse<usize>>,
+}
+
+impl TopK {
+    fn new(k: usize) -> Self {
+        TopK {
+            k,
+            // `k` may well be larger than the inventory
+            heap: BinaryHeap::with_capacity(k.min(1024)),
+        }
+    }
+
+    fn push(&mut self, value: usize) {
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(value));
+        } else if self.heap.peek().is_some_and(|Reverse(min)| *min < value) {
+            self.heap.pop();
+            self.heap.push(Reverse(value));
+        }
+    }
+
+    // Largest first
+    fn into_sorted_vec(self) -> Vec<usize> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(v)| v)
+            .collect()
+    }
+}
+
+fn top_calories<R: Read>(reader: R, k: usize) -> Result<Vec<usize>, InventoryError> {
+    let mut top = TopK::new(k);
+    for elf in ElfReader::new(reader) {
+        top.push(elf?.total_calories());
+    }
+    Ok(top.into_sorted_vec())
+}
+
+// Totals of every elf, for queries across the whole inventory
+struct Totals(Vec<usize>);
+
+impl FromIterator<usize> for Totals {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut totals = iter.into_iter().collect::<Vec<_>>();
+        totals.sort_unstable();
+        Totals(totals)
+    }
+}
+
+impl Totals {
+    // Nearest-rank percentile, `p` between 0 and 100
+    fn percentile(&self, p: f64) -> Option<usize> {
+        if self.0.is_empty() || !(0.0..=100.0).contains(&p) {
+            return None;
+        }
+        let rank = (p / 100.0 * self.0.len() as f64).ceil() as usize;
+        Some(self.0[rank.max(1) - 1])
+    }
 }
 
 #[test]
 fn part1() {
-    let elves = parse(INPUT);
-    println!("{:?}", elves);
-    let mut elves = elves
-        .iter()
-        .map(Elf::total_calories)
-        .collect::<Vec<usize>>();
-    println!("{:?}", elves);
-    elves.sort_unstable();
-    elves.reverse();
-    println!("{:?}", elves);
-    let result = elves.first().expect("at least one elf");
-    println!("{:?}", result);
+    let result = top_calories(INPUT.as_bytes(), 1).expect("a valid inventory");
+    println!("{:?}", result.first().expect("at least one elf"));
 }
 
 #[test]
 fn part2() {
-    let elves = parse(INPUT);
-    println!("{:?}", elves);
-    let mut elves = elves
+    let result = top_calories(INPUT.as_bytes(), 3)
+        .expect("a valid inventory")
         .iter()
-        .map(Elf::total_calories)
-        .collect::<Vec<usize>>();
-    println!("{:?}", elves);
-    elves.sort_unstable();
-    elves.reverse();
-    println!("{:?}", elves);
-    let result = elves.iter().take(3).sum::<usize>();
+        .sum::<usize>();
     println!("{:?}", result);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT_TEST: &str = "1000
+2000
+3000
+
+4000
+
+5000
+6000
+
+7000
+8000
+9000
+
+10000
+";
+
+    #[test]
+    fn it_handles_line_endings() {
+        let crlf = INPUT_TEST.replace('\n', "\r\n");
+        for input in [INPUT_TEST, crlf.as_str()] {
+            let elves = parse(input);
+            assert_eq!(elves.len(), 5);
+            assert_eq!(elves[3].calories, vec![7000, 8000, 9000]);
+            assert_eq!(
+                top_calories(input.as_bytes(), 3).unwrap(),
+                vec![24000, 11000, 10000]
+            );
+        }
+        assert_eq!(parse("\n\n1\n\n\n\n2").len(), 2);
+        assert_eq!(parse(INPUT).len(), 240);
+        assert_eq!(top_calories(INPUT.as_bytes(), 1).unwrap(), vec![70296]);
+        assert_eq!(
+            top_calories(INPUT.as_bytes(), 3)
+                .unwrap()
+                .iter()
+                .sum::<usize>(),
+            205381
+        );
+        assert_eq!(top_calories(INPUT_TEST.as_bytes(), 10).unwrap().len(), 5);
+        assert!(top_calories(INPUT_TEST.as_bytes(), 0).unwrap().is_empty());
+        assert_eq!(
+            top_calories(INPUT_TEST.as_bytes(), usize::MAX).unwrap(),
+            vec![24000, 11000, 10000, 6000, 4000]
+        );
+    }
+
+    #[test]
+    fn it_reports_invalid_lines() {
+        match top_calories("1\n\n2\nabc\n".as_bytes(), 1) {
+            Err(InventoryError::InvalidCalories { line, text }) => {
+                assert_eq!((line, text.as_str()), (4, "abc"))
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn it_summarizes_elves() {
+        let elves = parse(INPUT_TEST);
+        assert_eq!(
+            elves[0].summary(),
+            Some(Summary {
+                count: 3,
+                total: 6000,
+                min: 1000,
+                max: 3000,
+                mean: 2000.0,
+                median: 2000.0,
+            })
+        );
+        assert_eq!(
+            elves[2].summary(),
+            Some(Summary {
+                count: 2,
+                total: 11000,
+                min: 5000,
+                max: 6000,
+                mean: 5500.0,
+                median: 5500.0,
+            })
+        );
+        assert_eq!(Elf { calories: vec![] }.summary(), None);
+        assert_eq!(
+            elves[3].summary().unwrap().to_string(),
+            "3 items, total 24000, min 7000, max 9000, mean 8000.0, median 8000.0"
+        );
+    }
+
+    #[test]
+    fn it_queries_percentiles() {
+        let totals = parse(INPUT_TEST)
+            .iter()
+            .map(Elf::total_calories)
+            .collect::<Totals>();
+        assert_eq!(totals.percentile(0.0), Some(4000));
+        assert_eq!(totals.percentile(20.0), Some(4000));
+        assert_eq!(totals.percentile(50.0), Some(10000));
+        assert_eq!(totals.percentile(90.0), Some(24000));
+        assert_eq!(totals.percentile(100.0), Some(24000));
+        assert_eq!(totals.percentile(101.0), None);
+        assert_eq!(Totals(vec![]).percentile(50.0), None);
+    }
+}
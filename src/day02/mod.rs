@@ -1,176 +1,693 @@
+use std::collections::HashMap;
+use std::fmt;
+
 const INPUT: &str = include_str!("input.txt");
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Hand {
-    Rock,
-    Paper,
-    Scissors,
-}
-
-impl From<&str> for Hand {
-    fn from(value: &str) -> Self {
-        match value {
-            "A" | "X" => Hand::Rock,
-            "B" | "Y" => Hand::Paper,
-            "C" | "Z" => Hand::Scissors,
-            &_ => panic!("unknown value {value}"),
-        }
-    }
+// A gesture of a game with `gestures` gestures, arranged in a cycle where each
+// one beats the half of the others that come right before it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+struct Hand {
+    index: usize,
+    gestures: usize,
 }
 
-impl From<usize> for Hand {
-    fn from(value: usize) -> Self {
-        match value {
-            1 => Hand::Rock,
-            2 => Hand::Paper,
-            3 => Hand::Scissors,
-            _ => panic!("unknown value {value}"),
-        }
+impl Hand {
+    const ROCK: Hand = Hand::new(0, 3);
+    const PAPER: Hand = Hand::new(1, 3);
+    const SCISSORS: Hand = Hand::new(2, 3);
+
+    const fn new(index: usize, gestures: usize) -> Self {
+        Hand { index, gestures }
     }
-}
 
-impl Hand {
-    fn score(&self) -> usize {
-        match self {
-            Hand::Rock => 1,
-            Hand::Paper => 2,
-            Hand::Scissors => 3,
-        }
+    fn shifted(&self, by: usize) -> Hand {
+        Hand::new((self.index + by) % self.gestures, self.gestures)
     }
 
     fn fight(&self, other: &Hand) -> Outcome {
-        if (other.score() % 3) + 1 == self.score() {
-            Outcome::Win
-        } else if other.score() == self.score() {
+        let distance = (self.index + self.gestures - other.index) % self.gestures;
+        if distance == 0 {
             Outcome::Tie
+        } else if distance <= self.gestures / 2 {
+            Outcome::Win
         } else {
             Outcome::Loss
         }
     }
 
+    // With more than three gestures there are several hands for a given
+    // outcome, this picks the closest one in the cycle.
     fn from_outcome(other_hand: &Hand, outcome: &Outcome) -> Hand {
         match outcome {
             Outcome::Tie => *other_hand,
-            Outcome::Win => ((other_hand.score() % 3) + 1).into(),
-            Outcome::Loss => (((other_hand.score() + 1) % 3) + 1).into(),
+            Outcome::Win => other_hand.shifted(1),
+            Outcome::Loss => other_hand.shifted(other_hand.gestures - 1),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum GameError {
+    EvenGestures(usize),
+    DuplicateGesture(String),
+    UnknownGesture(String),
+    WrongLetterCount { expected: usize, found: usize },
+    DuplicateLetter(String),
+    WrongScoreCount { expected: usize, found: usize },
+    UnknownLetter { line: usize, letter: String },
+    MissingLetter(usize),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::EvenGestures(n) => {
+                write!(f, "a game needs an odd number of gestures, got {n}")
+            }
+            GameError::UnknownGesture(name) => write!(f, "unknown gesture {name}"),
+            GameError::WrongLetterCount { expected, found } => {
+                write!(f, "expected {expected} letters, got {found}")
+            }
+            GameError::DuplicateGesture(name) => write!(f, "gesture {name} is used twice"),
+            GameError::DuplicateLetter(letter) => write!(f, "letter {letter} is used twice"),
+            GameError::WrongScoreCount { expected, found } => {
+                write!(f, "expected {expected} hand scores, got {found}")
+            }
+            GameError::UnknownLetter { line, letter } => {
+                write!(f, "line {line}: unknown letter {letter}")
+            }
+            GameError::MissingLetter(line) => write!(f, "line {line}: expected two letters"),
+        }
+    }
+}
+
+// Gestures in cycle order, how the strategy guide letters map to them, and how
+// rounds are scored.
+#[derive(Debug)]
+struct Game {
+    gestures: Vec<String>,
+    opponent_letters: HashMap<String, Hand>,
+    my_letters: HashMap<String, Hand>,
+    outcome_letters: HashMap<String, Outcome>,
+    hand_scores: Vec<usize>,
+    win: usize,
+    tie: usize,
+    loss: usize,
+}
+
+impl Game {
+    // Every gesture is written with its own name by both players, hands score
+    // 1, 2, 3... in cycle order and outcomes 6/3/0.
+    fn new(gestures: &[&str]) -> Result<Self, GameError> {
+        if gestures.len().is_multiple_of(2) {
+            return Err(GameError::EvenGestures(gestures.len()));
+        }
+        for (i, name) in gestures.iter().enumerate() {
+            if gestures[..i].contains(name) {
+                return Err(GameError::DuplicateGesture(name.to_string()));
+            }
         }
+        let hands = (0..gestures.len())
+            .map(|i| (gestures[i].to_string(), Hand::new(i, gestures.len())))
+            .collect::<HashMap<_, _>>();
+        Ok(Game {
+            gestures: gestures.iter().map(|g| g.to_string()).collect(),
+            opponent_letters: hands.clone(),
+            my_letters: hands,
+            outcome_letters: [
+                ("X", Outcome::Loss),
+                ("Y", Outcome::Tie),
+                ("Z", Outcome::Win),
+            ]
+            .into_iter()
+            .map(|(l, o)| (l.to_string(), o))
+            .collect(),
+            hand_scores: (1..=gestures.len()).collect(),
+            win: 6,
+            tie: 3,
+            loss: 0,
+        })
+    }
+
+    fn classic() -> Self {
+        Game::new(&["Rock", "Paper", "Scissors"])
+            .and_then(|g| g.with_letters(&["A", "B", "C"], &["X", "Y", "Z"]))
+            .expect("a valid game")
+    }
+
+    fn rock_paper_scissors_lizard_spock() -> Self {
+        Game::new(&["Rock", "Spock", "Paper", "Lizard", "Scissors"]).expect("a valid game")
+    }
+
+    fn hand(&self, gesture: &str) -> Result<Hand, GameError> {
+        self.gestures
+            .iter()
+            .position(|g| g == gesture)
+            .map(|i| Hand::new(i, self.gestures.len()))
+            .ok_or_else(|| GameError::UnknownGesture(gesture.to_string()))
+    }
+
+    fn hands(&self) -> impl Iterator<Item = Hand> + '_ {
+        (0..self.gestures.len()).map(|i| Hand::new(i, self.gestures.len()))
+    }
+
+    fn name(&self, hand: Hand) -> &str {
+        &self.gestures[hand.index]
+    }
+
+    fn letters(&self, letters: &[&str]) -> Result<HashMap<String, Hand>, GameError> {
+        if letters.len() != self.gestures.len() {
+            return Err(GameError::WrongLetterCount {
+                expected: self.gestures.len(),
+                found: letters.len(),
+            });
+        }
+        let mut ret = HashMap::new();
+        for (hand, letter) in self.hands().zip(letters) {
+            if ret.insert(letter.to_string(), hand).is_some() {
+                return Err(GameError::DuplicateLetter(letter.to_string()));
+            }
+        }
+        Ok(ret)
+    }
+
+    // Letters used by each player, in cycle order of the gestures
+    fn with_letters(mut self, opponent: &[&str], mine: &[&str]) -> Result<Self, GameError> {
+        self.opponent_letters = self.letters(opponent)?;
+        self.my_letters = self.letters(mine)?;
+        Ok(self)
+    }
+
+    fn with_outcome_letters(mut self, loss: &str, tie: &str, win: &str) -> Result<Self, GameError> {
+        let letters = [
+            (loss, Outcome::Loss),
+            (tie, Outcome::Tie),
+            (win, Outcome::Win),
+        ];
+        self.outcome_letters = HashMap::new();
+        for (letter, outcome) in letters {
+            if self
+                .outcome_letters
+                .insert(letter.to_string(), outcome)
+                .is_some()
+            {
+                return Err(GameError::DuplicateLetter(letter.to_string()));
+            }
+        }
+        Ok(self)
+    }
+
+    fn with_scores(
+        mut self,
+        hands: &[usize],
+        loss: usize,
+        tie: usize,
+        win: usize,
+    ) -> Result<Self, GameError> {
+        if hands.len() != self.gestures.len() {
+            return Err(GameError::WrongScoreCount {
+                expected: self.gestures.len(),
+                found: hands.len(),
+            });
+        }
+        self.hand_scores = hands.to_vec();
+        (self.loss, self.tie, self.win) = (loss, tie, win);
+        Ok(self)
+    }
+
+    fn score(&self, my_hand: &Hand, outcome: &Outcome) -> usize {
+        self.hand_scores[my_hand.index]
+            + match outcome {
+                Outcome::Win => self.win,
+                Outcome::Tie => self.tie,
+                Outcome::Loss => self.loss,
+            }
+    }
+
+    fn lookup<T: Copy>(
+        line: usize,
+        letter: Option<&str>,
+        letters: &HashMap<String, T>,
+    ) -> Result<T, GameError> {
+        let letter = letter.ok_or(GameError::MissingLetter(line))?;
+        letters
+            .get(letter)
+            .copied()
+            .ok_or_else(|| GameError::UnknownLetter {
+                line,
+                letter: letter.to_string(),
+            })
+    }
+
+    fn parse_lines<T: Copy>(
+        &self,
+        input: &str,
+        second: &HashMap<String, T>,
+    ) -> Result<Vec<(Hand, T)>, GameError> {
+        input
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let mut letters = line.split_whitespace();
+                Ok((
+                    Self::lookup(i + 1, letters.next(), &self.opponent_letters)?,
+                    Self::lookup(i + 1, letters.next(), second)?,
+                ))
+            })
+            .collect()
+    }
+
+    // Second column read as my hand
+    fn parse_hands(&self, input: &str) -> Result<Vec<(Hand, Hand)>, GameError> {
+        self.parse_lines(input, &self.my_letters)
+    }
+
+    // Second column read as the outcome to reach
+    fn parse_outcomes(&self, input: &str) -> Result<Vec<(Hand, Outcome)>, GameError> {
+        self.parse_lines(input, &self.outcome_letters)
+    }
+}
+
+// What the second column of a guide means, one entry per letter
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Reading {
+    Hands(Vec<Hand>),
+    Outcomes(Vec<Outcome>),
+}
+
+#[derive(Debug)]
+struct Interpretation {
+    reading: Reading,
+    total: usize,
+}
+
+#[derive(Debug)]
+struct Analysis {
+    // letters of the second column, sorted
+    letters: Vec<String>,
+    // every reading of those letters, best total first
+    interpretations: Vec<Interpretation>,
+    best: usize,
+    worst: usize,
+    opponent_moves: Vec<(Hand, usize)>,
+}
+
+impl Analysis {
+    fn total(&self, reading: &Reading) -> Option<usize> {
+        self.interpretations
+            .iter()
+            .find(|i| &i.reading == reading)
+            .map(|i| i.total)
+    }
+
+    fn report(&self, game: &Game) -> String {
+        let moves = self
+            .opponent_moves
+            .iter()
+            .map(|(hand, count)| format!("{} {count}", game.name(*hand)))
+            .collect::<Vec<_>>();
+        let mut ret = format!(
+            "opponent: {}\nbest {}, worst {}\n",
+            moves.join(", "),
+            self.best,
+            self.worst
+        );
+        for interpretation in &self.interpretations {
+            let meanings = match &interpretation.reading {
+                Reading::Hands(hands) => hands
+                    .iter()
+                    .map(|h| game.name(*h).to_string())
+                    .collect::<Vec<_>>(),
+                Reading::Outcomes(outcomes) => outcomes.iter().map(|o| format!("{o:?}")).collect(),
+            };
+            let mapping = self
+                .letters
+                .iter()
+                .zip(meanings)
+                .map(|(letter, meaning)| format!("{letter}={meaning}"))
+                .collect::<Vec<_>>();
+            ret.push_str(&format!(
+                "{}: {}\n",
+                mapping.join(" "),
+                interpretation.total
+            ));
+        }
+        ret
+    }
+}
+
+// Every way to pick `k` different values out of 0..n, in order
+fn arrangements(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    let mut ret = vec![];
+    for prefix in arrangements(n, k - 1) {
+        for i in (0..n).filter(|i| !prefix.contains(i)) {
+            let mut next = prefix.clone();
+            next.push(i);
+            ret.push(next);
+        }
+    }
+    ret
+}
+
+impl Game {
+    fn analyze(&self, input: &str) -> Result<Analysis, GameError> {
+        let mut rounds = vec![];
+        let mut letters: Vec<String> = vec![];
+        for (i, line) in input.lines().enumerate() {
+            let mut columns = line.split_whitespace();
+            let other = Self::lookup(i + 1, columns.next(), &self.opponent_letters)?;
+            let letter = columns.next().ok_or(GameError::MissingLetter(i + 1))?;
+            rounds.push((other, letter));
+            if !letters.iter().any(|l| l == letter) {
+                letters.push(letter.to_string());
+            }
+        }
+        letters.sort_unstable();
+        let rounds = rounds
+            .into_iter()
+            .map(|(other, letter)| {
+                (
+                    other,
+                    letters
+                        .iter()
+                        .position(|l| l == letter)
+                        .expect("a known letter"),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let hands = self.hands().collect::<Vec<_>>();
+        let outcomes = [Outcome::Loss, Outcome::Tie, Outcome::Win];
+        let mut readings = arrangements(hands.len(), letters.len())
+            .into_iter()
+            .map(|a| Reading::Hands(a.into_iter().map(|i| hands[i]).collect()))
+            .collect::<Vec<_>>();
+        if letters.len() <= outcomes.len() {
+            readings.extend(
+                arrangements(outcomes.len(), letters.len())
+                    .into_iter()
+                    .map(|a| Reading::Outcomes(a.into_iter().map(|i| outcomes[i]).collect())),
+            );
+        }
+        let mut interpretations = readings
+            .into_iter()
+            .map(|reading| Interpretation {
+                total: self.total(&rounds, &reading),
+                reading,
+            })
+            .collect::<Vec<_>>();
+        interpretations.sort_by_key(|i| std::cmp::Reverse(i.total));
+
+        let round_scores = |other: &Hand| {
+            hands
+                .iter()
+                .map(|h| self.score(h, &h.fight(other)))
+                .collect::<Vec<_>>()
+        };
+        let (best, worst) = rounds.iter().fold((0, 0), |(best, worst), (other, _)| {
+            let scores = round_scores(other);
+            (
+                best + scores.iter().max().expect("at least one gesture"),
+                worst + scores.iter().min().expect("at least one gesture"),
+            )
+        });
+        let opponent_moves = hands
+            .iter()
+            .map(|h| (*h, rounds.iter().filter(|(other, _)| other == h).count()))
+            .collect();
+
+        Ok(Analysis {
+            letters,
+            interpretations,
+            best,
+            worst,
+            opponent_moves,
+        })
+    }
+
+    fn total(&self, rounds: &[(Hand, usize)], reading: &Reading) -> usize {
+        rounds
+            .iter()
+            .map(|(other, letter)| match reading {
+                Reading::Hands(hands) => self.score(&hands[*letter], &hands[*letter].fight(other)),
+                Reading::Outcomes(outcomes) => {
+                    let outcome = &outcomes[*letter];
+                    self.score(&Hand::from_outcome(other, outcome), outcome)
+                }
+            })
+            .sum()
     }
 }
 
 #[test]
 fn test_fight() {
-    assert_eq!(Hand::Paper.fight(&Hand::Scissors), Outcome::Loss);
-    assert_eq!(Hand::Paper.fight(&Hand::Rock), Outcome::Win);
-    assert_eq!(Hand::Paper.fight(&Hand::Paper), Outcome::Tie);
-    assert_eq!(Hand::Rock.fight(&Hand::Scissors), Outcome::Win);
-    assert_eq!(Hand::Rock.fight(&Hand::Paper), Outcome::Loss);
-    assert_eq!(Hand::Rock.fight(&Hand::Rock), Outcome::Tie);
-    assert_eq!(Hand::Scissors.fight(&Hand::Scissors), Outcome::Tie);
-    assert_eq!(Hand::Scissors.fight(&Hand::Rock), Outcome::Loss);
-    assert_eq!(Hand::Scissors.fight(&Hand::Paper), Outcome::Win);
+    assert_eq!(Hand::PAPER.fight(&Hand::SCISSORS), Outcome::Loss);
+    assert_eq!(Hand::PAPER.fight(&Hand::ROCK), Outcome::Win);
+    assert_eq!(Hand::PAPER.fight(&Hand::PAPER), Outcome::Tie);
+    assert_eq!(Hand::ROCK.fight(&Hand::SCISSORS), Outcome::Win);
+    assert_eq!(Hand::ROCK.fight(&Hand::PAPER), Outcome::Loss);
+    assert_eq!(Hand::ROCK.fight(&Hand::ROCK), Outcome::Tie);
+    assert_eq!(Hand::SCISSORS.fight(&Hand::SCISSORS), Outcome::Tie);
+    assert_eq!(Hand::SCISSORS.fight(&Hand::ROCK), Outcome::Loss);
+    assert_eq!(Hand::SCISSORS.fight(&Hand::PAPER), Outcome::Win);
 }
 
 #[test]
 fn test_from_outcome() {
-    assert_eq!(Hand::from_outcome(&Hand::Rock, &Outcome::Win), Hand::Paper);
-    assert_eq!(Hand::from_outcome(&Hand::Rock, &Outcome::Tie), Hand::Rock);
+    assert_eq!(Hand::from_outcome(&Hand::ROCK, &Outcome::Win), Hand::PAPER);
+    assert_eq!(Hand::from_outcome(&Hand::ROCK, &Outcome::Tie), Hand::ROCK);
     assert_eq!(
-        Hand::from_outcome(&Hand::Rock, &Outcome::Loss),
-        Hand::Scissors
+        Hand::from_outcome(&Hand::ROCK, &Outcome::Loss),
+        Hand::SCISSORS
     );
     assert_eq!(
-        Hand::from_outcome(&Hand::Paper, &Outcome::Win),
-        Hand::Scissors
+        Hand::from_outcome(&Hand::PAPER, &Outcome::Win),
+        Hand::SCISSORS
     );
-    assert_eq!(Hand::from_outcome(&Hand::Paper, &Outcome::Tie), Hand::Paper);
-    assert_eq!(Hand::from_outcome(&Hand::Paper, &Outcome::Loss), Hand::Rock);
+    assert_eq!(Hand::from_outcome(&Hand::PAPER, &Outcome::Tie), Hand::PAPER);
+    assert_eq!(Hand::from_outcome(&Hand::PAPER, &Outcome::Loss), Hand::ROCK);
     assert_eq!(
-        Hand::from_outcome(&Hand::Scissors, &Outcome::Win),
-        Hand::Rock
+        Hand::from_outcome(&Hand::SCISSORS, &Outcome::Win),
+        Hand::ROCK
     );
     assert_eq!(
-        Hand::from_outcome(&Hand::Scissors, &Outcome::Tie),
-        Hand::Scissors
+        Hand::from_outcome(&Hand::SCISSORS, &Outcome::Tie),
+        Hand::SCISSORS
     );
     assert_eq!(
-        Hand::from_outcome(&Hand::Scissors, &Outcome::Loss),
-        Hand::Paper
+        Hand::from_outcome(&Hand::SCISSORS, &Outcome::Loss),
+        Hand::PAPER
     );
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum Outcome {
     Win,
     Loss,
     Tie,
 }
 
-impl From<&str> for Outcome {
-    fn from(value: &str) -> Self {
-        match value {
-            "X" => Outcome::Loss,
-            "Y" => Outcome::Tie,
-            "Z" => Outcome::Win,
-            &_ => panic!("unknown value {value}"),
-        }
-    }
-}
-
-impl Outcome {
-    fn score(&self) -> usize {
-        match self {
-            Outcome::Win => 6,
-            Outcome::Tie => 3,
-            Outcome::Loss => 0,
-        }
-    }
-}
-
 fn parse1(input: &str) -> Vec<(Hand, Hand)> {
-    input
-        .lines()
-        .map(|line| {
-            let mut hands = line.split_whitespace();
-            (
-                hands.next().expect("one hand").into(),
-                hands.next().expect("second hand").into(),
-            )
-        })
-        .collect()
+    Game::classic().parse_hands(input).expect("a valid guide")
 }
 
 fn parse2(input: &str) -> Vec<(Hand, Outcome)> {
-    input
-        .lines()
-        .map(|line| {
-            let mut game = line.split_whitespace();
-            (
-                game.next().expect("other hand").into(),
-                game.next().expect("outcome").into(),
-            )
-        })
-        .collect()
+    Game::classic()
+        .parse_outcomes(input)
+        .expect("a valid guide")
 }
 
 #[test]
 fn part1() {
+    let game = Game::classic();
     let hands = parse1(INPUT);
     let result = hands
         .iter()
-        .map(|(other_hand, my_hand)| my_hand.fight(other_hand).score() + my_hand.score())
+        .map(|(other_hand, my_hand)| game.score(my_hand, &my_hand.fight(other_hand)))
         .sum::<usize>();
     println!("{:?}", result);
 }
 
 #[test]
 fn part2() {
+    let game = Game::classic();
     let games = parse2(INPUT);
     let result = games
         .iter()
-        .map(|(other_hand, outcome)| {
-            let my_hand = Hand::from_outcome(other_hand, outcome);
-            my_hand.score() + outcome.score()
-        })
+        .map(|(other_hand, outcome)| game.score(&Hand::from_outcome(other_hand, outcome), outcome))
         .sum::<usize>();
     println!("{:?}", result);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT_TEST: &str = "A Y
+B X
+C Z";
+
+    #[test]
+    fn it_scores_the_classic_game() {
+        let game = Game::classic();
+        let total = |rounds: Vec<(Hand, Outcome)>| {
+            rounds
+                .iter()
+                .map(|(other, outcome)| game.score(&Hand::from_outcome(other, outcome), outcome))
+                .sum::<usize>()
+        };
+        let rounds = parse1(INPUT_TEST);
+        assert_eq!(
+            rounds
+                .iter()
+                .map(|(other, mine)| game.score(mine, &mine.fight(other)))
+                .sum::<usize>(),
+            15
+        );
+        assert_eq!(total(parse2(INPUT_TEST)), 12);
+        assert_eq!(total(parse2(INPUT)), 14060);
+    }
+
+    #[test]
+    fn it_plays_rock_paper_scissors_lizard_spock() {
+        let game = Game::rock_paper_scissors_lizard_spock();
+        let hand = |name| game.hand(name).unwrap();
+        let beats = [
+            ("Scissors", "Paper"),
+            ("Paper", "Rock"),
+            ("Rock", "Lizard"),
+            ("Lizard", "Spock"),
+            ("Spock", "Scissors"),
+            ("Scissors", "Lizard"),
+            ("Lizard", "Paper"),
+            ("Paper", "Spock"),
+            ("Spock", "Rock"),
+            ("Rock", "Scissors"),
+        ];
+        for (winner, loser) in beats {
+            assert_eq!(hand(winner).fight(&hand(loser)), Outcome::Win);
+            assert_eq!(hand(loser).fight(&hand(winner)), Outcome::Loss);
+        }
+        for other in game.hands() {
+            assert_eq!(other.fight(&other), Outcome::Tie);
+            for outcome in [Outcome::Win, Outcome::Tie, Outcome::Loss] {
+                assert_eq!(Hand::from_outcome(&other, &outcome).fight(&other), outcome);
+            }
+        }
+        assert_eq!(
+            game.name(Hand::from_outcome(&hand("Rock"), &Outcome::Win)),
+            "Spock"
+        );
+
+        let game = Game::rock_paper_scissors_lizard_spock()
+            .with_letters(&["A", "B", "C", "D", "E"], &["V", "W", "X", "Y", "Z"])
+            .and_then(|g| g.with_outcome_letters("L", "T", "W"))
+            .and_then(|g| g.with_scores(&[5, 4, 3, 2, 1], 0, 1, 2))
+            .unwrap();
+        let rounds = game.parse_hands("A W\nD Z").unwrap();
+        assert_eq!(
+            rounds
+                .iter()
+                .map(|(other, mine)| game.score(mine, &mine.fight(other)))
+                .sum::<usize>(),
+            4 + 2 + 1 + 2
+        );
+        let rounds = game.parse_outcomes("E W\nC T").unwrap();
+        assert_eq!(rounds[0], (game.hand("Scissors").unwrap(), Outcome::Win));
+        assert_eq!(
+            game.score(
+                &Hand::from_outcome(&rounds[0].0, &rounds[0].1),
+                &rounds[0].1
+            ),
+            7
+        );
+    }
+
+    #[test]
+    fn it_analyzes_guides() {
+        let game = Game::classic();
+        let analysis = game.analyze(INPUT_TEST).unwrap();
+        assert_eq!(analysis.letters, vec!["X", "Y", "Z"]);
+        assert_eq!(analysis.interpretations.len(), 12);
+        let as_hands = Reading::Hands(vec![Hand::ROCK, Hand::PAPER, Hand::SCISSORS]);
+        let as_outcomes = Reading::Outcomes(vec![Outcome::Loss, Outcome::Tie, Outcome::Win]);
+        assert_eq!(analysis.total(&as_hands), Some(15));
+        assert_eq!(analysis.total(&as_outcomes), Some(12));
+        assert_eq!((analysis.best, analysis.worst), (24, 6));
+        assert_eq!(
+            analysis.opponent_moves,
+            vec![(Hand::ROCK, 1), (Hand::PAPER, 1), (Hand::SCISSORS, 1)]
+        );
+        let report = analysis.report(&game);
+        assert!(report.starts_with("opponent: Rock 1, Paper 1, Scissors 1\nbest 24, worst 6\n"));
+        assert!(report.contains("X=Rock Y=Paper Z=Scissors: 15\n"));
+        assert!(report.contains("X=Loss Y=Tie Z=Win: 12\n"));
+
+        let analysis = game.analyze(INPUT).unwrap();
+        assert_eq!(analysis.total(&as_hands), Some(10624));
+        assert_eq!(analysis.total(&as_outcomes), Some(14060));
+        let totals = analysis
+            .interpretations
+            .iter()
+            .map(|i| i.total)
+            .collect::<Vec<_>>();
+        assert!(totals.windows(2).all(|w| w[0] >= w[1]));
+        assert!(analysis.best >= totals[0] && analysis.worst <= totals[totals.len() - 1]);
+        assert_eq!(
+            analysis
+                .opponent_moves
+                .iter()
+                .map(|(_, n)| n)
+                .sum::<usize>(),
+            INPUT.lines().count()
+        );
+
+        let game = Game::rock_paper_scissors_lizard_spock();
+        let analysis = game.analyze("Rock Spock\nPaper Rock").unwrap();
+        assert_eq!(analysis.interpretations.len(), 5 * 4 + 3 * 2);
+    }
+
+    #[test]
+    fn it_rejects_bad_games() {
+        assert_eq!(
+            Game::new(&["Rock", "Paper"]).unwrap_err(),
+            GameError::EvenGestures(2)
+        );
+        assert_eq!(
+            Game::new(&["Rock", "Rock", "Paper"]).unwrap_err(),
+            GameError::DuplicateGesture("Rock".to_string())
+        );
+        let game = Game::classic();
+        assert_eq!(
+            game.hand("Lizard").unwrap_err(),
+            GameError::UnknownGesture("Lizard".to_string())
+        );
+        assert_eq!(
+            game.parse_hands("A X\nB Q").unwrap_err(),
+            GameError::UnknownLetter {
+                line: 2,
+                letter: "Q".to_string()
+            }
+        );
+        assert_eq!(
+            game.parse_outcomes("A").unwrap_err(),
+            GameError::MissingLetter(1)
+        );
+        assert_eq!(
+            Game::classic()
+                .with_letters(&["A", "A", "C"], &["X", "Y", "Z"])
+                .unwrap_err(),
+            GameError::DuplicateLetter("A".to_string())
+        );
+        assert_eq!(
+            Game::classic().with_scores(&[1, 2], 0, 3, 6).unwrap_err(),
+            GameError::WrongScoreCount {
+                expected: 3,
+                found: 2
+            }
+        );
+    }
+}
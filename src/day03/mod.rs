@@ -1,5 +1,3 @@
-use std::collections::HashSet;
-
 const INPUT: &str = include_str!("input.txt");
 
 #[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
@@ -21,6 +19,14 @@ impl Stuff {
             panic!("Not a valid char `{:?}`", self.0)
         }
     }
+
+    fn from_priority(priority: usize) -> Self {
+        match priority {
+            1..=26 => Stuff((b'a' + priority as u8 - 1) as char),
+            27..=52 => Stuff((b'A' + priority as u8 - 27) as char),
+            _ => panic!("Not a valid priority {priority}"),
+        }
+    }
 }
 
 #[test]
@@ -31,33 +37,98 @@ fn test_priority() {
     assert_eq!(Stuff('z').priority(), 26);
 }
 
+// Set of items as a bitmask, bit `priority - 1` standing for the item
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+struct Items(u64);
+
+impl FromIterator<Stuff> for Items {
+    fn from_iter<T: IntoIterator<Item = Stuff>>(iter: T) -> Self {
+        Items(
+            iter.into_iter()
+                .fold(0, |mask, s| mask | 1 << (s.priority() - 1)),
+        )
+    }
+}
+
+impl Items {
+    const ALL: Items = Items((1 << 52) - 1);
+
+    fn intersection(&self, other: &Items) -> Items {
+        Items(self.0 & other.0)
+    }
+
+    fn union(&self, other: &Items) -> Items {
+        Items(self.0 | other.0)
+    }
+
+    fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Stuff> + '_ {
+        (1..=52)
+            .filter(|p| self.0 & 1 << (p - 1) != 0)
+            .map(Stuff::from_priority)
+    }
+
+    // The only item of the set
+    fn single(&self) -> Result<Stuff, CommonItemError> {
+        match self.len() {
+            0 => Err(CommonItemError::NoCommonItem),
+            1 => Ok(Stuff::from_priority(self.0.trailing_zeros() as usize + 1)),
+            _ => Err(CommonItemError::SeveralCommonItems(self.iter().collect())),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum CommonItemError {
+    NoCommonItem,
+    SeveralCommonItems(Vec<Stuff>),
+    EmptyGroup,
+    IncompleteGroup(usize),
+}
+
 #[derive(Debug)]
-struct Sack(Vec<Stuff>, Vec<Stuff>);
+struct Sack(Items, Items);
 
 impl From<(Vec<Stuff>, Vec<Stuff>)> for Sack {
     fn from(value: (Vec<Stuff>, Vec<Stuff>)) -> Self {
-        Sack(value.0, value.1)
+        Sack(value.0.into_iter().collect(), value.1.into_iter().collect())
     }
 }
 
 impl Sack {
-    fn duplicated(self) -> Stuff {
-        let a = self.0.into_iter().collect::<HashSet<Stuff>>();
-        let b = self.1.into_iter().collect::<HashSet<Stuff>>();
-        a.intersection(&b)
-            .copied()
-            .collect::<Vec<Stuff>>()
-            .remove(0)
+    fn duplicated(&self) -> Result<Stuff, CommonItemError> {
+        self.0.intersection(&self.1).single()
+    }
+
+    fn items(&self) -> Items {
+        self.0.union(&self.1)
     }
 }
 
-struct MergedSack(Vec<Stuff>);
+// The only item carried by every sack of the group
+fn badge(group: &[Sack]) -> Result<Stuff, CommonItemError> {
+    if group.is_empty() {
+        return Err(CommonItemError::EmptyGroup);
+    }
+    group
+        .iter()
+        .fold(Items::ALL, |common, sack| {
+            common.intersection(&sack.items())
+        })
+        .single()
+}
 
-impl MergedSack {
-    fn from_sack(mut sack: Sack) -> MergedSack {
-        sack.0.append(&mut (sack.1));
-        MergedSack(sack.0)
+fn badges(sacks: &[Sack], group_size: usize) -> Result<Vec<Stuff>, CommonItemError> {
+    if group_size == 0 {
+        return Err(CommonItemError::EmptyGroup);
+    }
+    if !sacks.len().is_multiple_of(group_size) {
+        return Err(CommonItemError::IncompleteGroup(sacks.len() % group_size));
     }
+    sacks.chunks(group_size).map(badge).collect()
 }
 
 fn parse(input: &str) -> Vec<Sack> {
@@ -82,8 +153,8 @@ fn parse(input: &str) -> Vec<Sack> {
 fn part1() {
     let sacks = parse(INPUT);
     let sum_prios = sacks
-        .into_iter()
-        .map(|sack| sack.duplicated().priority())
+        .iter()
+        .map(|sack| sack.duplicated().expect("one duplicated item").priority())
         .sum::<usize>();
     println!("{:?}", sum_prios);
 }
@@ -91,20 +162,85 @@ fn part1() {
 #[test]
 fn part2() {
     let sacks = parse(INPUT);
-    let merged_sacks = sacks
-        .into_iter()
-        .map(|s| MergedSack::from_sack(s))
-        .collect::<Vec<_>>();
-    let merged_prio_sum = merged_sacks
-        .chunks(3)
-        .map(|c| {
-            let a = &c[0].0.iter().copied().collect::<HashSet<_>>();
-            let b = &c[1].0.iter().copied().collect::<HashSet<_>>();
-            let c = &c[2].0.iter().copied().collect::<HashSet<_>>();
-            let i1 = a.intersection(&b).copied().collect::<HashSet<_>>();
-            let i2 = b.intersection(&c).copied().collect::<HashSet<_>>();
-            i1.intersection(&i2).copied().collect::<Vec<_>>()[0].priority()
-        })
+    let merged_prio_sum = badges(&sacks, 3)
+        .expect("one badge per group")
+        .iter()
+        .map(Stuff::priority)
         .sum::<usize>();
     println!("{:?}", merged_prio_sum);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT_TEST: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw";
+
+    #[test]
+    fn test_items() {
+        let items = "abcZ".chars().map(Stuff).collect::<Items>();
+        assert_eq!(items.len(), 4);
+        assert_eq!(items.iter().map(|s| s.0).collect::<String>(), "abcZ");
+        let other = "cZz".chars().map(Stuff).collect::<Items>();
+        assert_eq!(items.intersection(&other).len(), 2);
+        assert_eq!(items.union(&other).len(), 5);
+        assert_eq!(Items::ALL.len(), 52);
+        for p in 1..=52 {
+            assert_eq!(Stuff::from_priority(p).priority(), p);
+        }
+    }
+
+    #[test]
+    fn it_finds_duplicates_and_badges() {
+        let sacks = parse(INPUT_TEST);
+        let duplicated = sacks
+            .iter()
+            .map(|s| s.duplicated().unwrap().0)
+            .collect::<String>();
+        assert_eq!(duplicated, "pLPvts");
+        assert_eq!(badges(&sacks, 3).unwrap(), vec![Stuff('r'), Stuff('Z')]);
+        assert_eq!(
+            badge(&sacks[..1]),
+            Err(CommonItemError::SeveralCommonItems(
+                sacks[0].items().iter().collect()
+            ))
+        );
+        assert_eq!(
+            badge(&sacks[..2]).unwrap_err(),
+            CommonItemError::SeveralCommonItems("frsFM".chars().map(Stuff).collect())
+        );
+        assert_eq!(badge(&sacks), Err(CommonItemError::NoCommonItem));
+        assert_eq!(badges(&sacks, 6), Err(CommonItemError::NoCommonItem));
+
+        let sacks = parse(INPUT);
+        let total =
+            |size| badges(&sacks, size).map(|b| b.iter().map(Stuff::priority).sum::<usize>());
+        assert_eq!(total(3), Ok(2689));
+        assert!(sacks.len().is_multiple_of(6));
+        assert!(total(6).is_err());
+    }
+
+    #[test]
+    fn it_rejects_bad_groups() {
+        let sacks = parse(INPUT_TEST);
+        assert_eq!(badge(&[]), Err(CommonItemError::EmptyGroup));
+        assert_eq!(badges(&sacks, 0), Err(CommonItemError::EmptyGroup));
+        assert_eq!(badges(&sacks, 4), Err(CommonItemError::IncompleteGroup(2)));
+        assert_eq!(
+            parse("abcd")[0].duplicated(),
+            Err(CommonItemError::NoCommonItem)
+        );
+        assert_eq!(
+            parse("abab")[0].duplicated(),
+            Err(CommonItemError::SeveralCommonItems(vec![
+                Stuff('a'),
+                Stuff('b')
+            ]))
+        );
+    }
+}
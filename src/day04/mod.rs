@@ -1,6 +1,8 @@
+use std::str::FromStr;
+
 const INPUT: &str = include_str!("input.txt");
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 struct Range {
     start: usize,
     end: usize,
@@ -12,13 +14,49 @@ impl Range {
     }
 
     fn overlaps(&self, other: &Range) -> bool {
-        (other.start <= self.start && self.start <= other.end)
-            || (other.start <= self.end && self.end <= other.end)
-            || (self.start <= other.start && other.start <= self.end)
-            || (self.start <= other.end && other.end <= self.end)
+        self.start <= other.end && other.start <= self.end
+    }
+
+    fn len(&self) -> usize {
+        self.end - self.start + 1
     }
 }
 
+#[derive(Debug, Eq, PartialEq)]
+enum RangeError {
+    Invalid(String),
+    Reversed { start: usize, end: usize },
+}
+
+impl FromStr for Range {
+    type Err = RangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || RangeError::Invalid(s.to_string());
+        let (start, end) = s.split_once('-').ok_or_else(invalid)?;
+        let start = start.parse().map_err(|_| invalid())?;
+        let end = end.parse().map_err(|_| invalid())?;
+        if start > end {
+            return Err(RangeError::Reversed { start, end });
+        }
+        Ok(Range { start, end })
+    }
+}
+
+#[test]
+fn test_parse_range() {
+    assert_eq!("2-4".parse(), Ok(Range { start: 2, end: 4 }));
+    assert_eq!("3-3".parse(), Ok(Range { start: 3, end: 3 }));
+    assert_eq!(
+        "5-3".parse::<Range>(),
+        Err(RangeError::Reversed { start: 5, end: 3 })
+    );
+    assert_eq!(
+        "5".parse::<Range>(),
+        Err(RangeError::Invalid("5".to_string()))
+    );
+}
+
 #[test]
 fn test_contains() {
     assert!(Range { start: 1, end: 3 }.contains(&Range { start: 2, end: 3 }));
@@ -34,32 +72,250 @@ fn test_overlap() {
     assert!(!Range { start: 5, end: 7 }.overlaps(&Range { start: 1, end: 3 }));
 }
 
-fn parse(input: &str) -> Vec<(Range, Range)> {
+// Disjoint, non-adjacent ranges sorted by start.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+struct IntervalSet(Vec<Range>);
+
+impl FromIterator<Range> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item = Range>>(iter: T) -> Self {
+        let mut ranges = iter.into_iter().collect::<Vec<_>>();
+        ranges.sort_unstable_by_key(|r| r.start);
+        let mut merged: Vec<Range> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end + 1 => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        IntervalSet(merged)
+    }
+}
+
+impl IntervalSet {
+    fn union(&self, other: &IntervalSet) -> IntervalSet {
+        self.0.iter().chain(other.0.iter()).copied().collect()
+    }
+
+    fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ret = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.0.len() && j < other.0.len() {
+            let (a, b) = (self.0[i], other.0[j]);
+            if a.overlaps(&b) {
+                ret.push(Range {
+                    start: a.start.max(b.start),
+                    end: a.end.min(b.end),
+                });
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet(ret)
+    }
+
+    fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ret = vec![];
+        let mut j = 0;
+        for range in &self.0 {
+            let mut start = range.start;
+            while j < other.0.len() && other.0[j].end < start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.0.len() && other.0[k].start <= range.end {
+                if other.0[k].start > start {
+                    ret.push(Range {
+                        start,
+                        end: other.0[k].start - 1,
+                    });
+                }
+                start = start.max(other.0[k].end + 1);
+                k += 1;
+            }
+            if start <= range.end {
+                ret.push(Range {
+                    start,
+                    end: range.end,
+                });
+            }
+        }
+        IntervalSet(ret)
+    }
+
+    fn len(&self) -> usize {
+        self.0.iter().map(Range::len).sum()
+    }
+
+    fn contains_point(&self, section: usize) -> bool {
+        let idx = self.0.partition_point(|r| r.end < section);
+        self.0.get(idx).is_some_and(|r| r.start <= section)
+    }
+}
+
+// Sweep over range boundaries, returning the sections covered by more than
+// `k` ranges along with how many ranges cover them.
+fn covered_by_more_than(ranges: &[Range], k: usize) -> Vec<(Range, usize)> {
+    let mut events = ranges
+        .iter()
+        .flat_map(|r| [(r.start, 1), (r.end + 1, -1)])
+        .collect::<Vec<(usize, isize)>>();
+    events.sort_unstable();
+    let mut ret = vec![];
+    let mut count = 0;
+    let mut prev = 0;
+    for (section, delta) in events {
+        if section > prev && count > k as isize {
+            ret.push((
+                Range {
+                    start: prev,
+                    end: section - 1,
+                },
+                count as usize,
+            ));
+        }
+        count += delta;
+        prev = section;
+    }
+    ret
+}
+
+#[test]
+fn test_interval_set() {
+    let a = [
+        Range { start: 1, end: 3 },
+        Range { start: 4, end: 6 },
+        Range { start: 10, end: 12 },
+    ]
+    .into_iter()
+    .collect::<IntervalSet>();
+    assert_eq!(
+        a.0,
+        vec![Range { start: 1, end: 6 }, Range { start: 10, end: 12 }]
+    );
+    assert_eq!(a.len(), 9);
+    let b = [Range { start: 5, end: 11 }]
+        .into_iter()
+        .collect::<IntervalSet>();
+    assert_eq!(a.union(&b).0, vec![Range { start: 1, end: 12 }]);
+    assert_eq!(
+        a.intersection(&b).0,
+        vec![Range { start: 5, end: 6 }, Range { start: 10, end: 11 }]
+    );
+    assert_eq!(
+        a.difference(&b).0,
+        vec![Range { start: 1, end: 4 }, Range { start: 12, end: 12 }]
+    );
+    assert_eq!(b.difference(&a).0, vec![Range { start: 7, end: 9 }]);
+    assert!(a.contains_point(6));
+    assert!(!a.contains_point(7));
+    assert!(a.contains_point(12));
+    assert!(!a.contains_point(13));
+}
+
+#[test]
+fn test_covered_by_more_than() {
+    let input = parse("2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8");
+    let ranges = input
+        .iter()
+        .flat_map(|g| g.0.iter().copied())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        covered_by_more_than(&ranges, 4),
+        vec![
+            (Range { start: 3, end: 3 }, 5),
+            (Range { start: 4, end: 4 }, 7),
+            (Range { start: 5, end: 5 }, 7),
+            (Range { start: 6, end: 6 }, 8),
+            (Range { start: 7, end: 7 }, 6),
+        ]
+    );
+    assert!(covered_by_more_than(&ranges, 8).is_empty());
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Group(Vec<Range>);
+
+impl Group {
+    fn any_contains(&self) -> bool {
+        let mut ranges = self.0.clone();
+        // wider ranges first among equal starts, so a container always
+        // comes before what it contains
+        ranges.sort_unstable_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+        let mut max_end = None;
+        for range in ranges {
+            if max_end.is_some_and(|end| range.end <= end) {
+                return true;
+            }
+            max_end = max_end.max(Some(range.end));
+        }
+        false
+    }
+
+    fn all_overlap(&self) -> bool {
+        let latest_start = self.0.iter().map(|r| r.start).max();
+        let earliest_end = self.0.iter().map(|r| r.end).min();
+        latest_start <= earliest_end
+    }
+
+    // Indices of the fewest assignments to drop so the remaining ones are
+    // pairwise disjoint: greedily keep the range that ends first.
+    fn to_drop(&self) -> Vec<usize> {
+        let mut order = (0..self.0.len()).collect::<Vec<_>>();
+        order.sort_unstable_by_key(|i| self.0[*i].end);
+        let mut last_end = None;
+        let mut dropped = vec![];
+        for i in order {
+            if last_end.is_some_and(|end| self.0[i].start <= end) {
+                dropped.push(i);
+            } else {
+                last_end = Some(self.0[i].end);
+            }
+        }
+        dropped.sort_unstable();
+        dropped
+    }
+}
+
+#[test]
+fn test_group() {
+    let group = parse("2-8,3-7,6-6,1-2").remove(0);
+    assert!(group.any_contains());
+    assert!(!group.all_overlap());
+    assert_eq!(group.to_drop(), vec![0, 1]);
+
+    let group = parse("1-5,2-6,3-7,5-5").remove(0);
+    assert!(group.any_contains());
+    assert!(group.all_overlap());
+    assert_eq!(group.to_drop().len(), 3);
+
+    let group = parse("1-3,3-5,1-5").remove(0);
+    assert!(group.any_contains());
+    let group = parse("1-3,2-5,4-6").remove(0);
+    assert!(!group.any_contains());
+    assert!(!group.all_overlap());
+    assert_eq!(group.to_drop(), vec![1]);
+
+    let group = parse("4-4").remove(0);
+    assert!(!group.any_contains());
+    assert!(group.all_overlap());
+    assert!(group.to_drop().is_empty());
+}
+
+fn parse(input: &str) -> Vec<Group> {
     input
         .lines()
         .map(|line| {
-            let mut ranges = line
-                .split(',')
-                .map(|range_str| {
-                    let mut parts = range_str.split('-');
-                    Range {
-                        start: parts
-                            .next()
-                            .expect("a start")
-                            .parse()
-                            .expect("a number repr"),
-                        end: parts
-                            .next()
-                            .expect("an end")
+            Group(
+                line.split(',')
+                    .map(|range_str| {
+                        range_str
                             .parse()
-                            .expect("a number repr"),
-                    }
-                })
-                .collect::<Vec<Range>>()
-                .into_iter();
-            (
-                ranges.next().expect("a range"),
-                ranges.next().expect("another range"),
+                            .unwrap_or_else(|e| panic!("invalid range {range_str:?}: {e:?}"))
+                    })
+                    .collect(),
             )
         })
         .collect()
@@ -68,19 +324,24 @@ fn parse(input: &str) -> Vec<(Range, Range)> {
 #[test]
 fn part1() {
     let input = parse(INPUT);
-    let result = input
-        .iter()
-        .filter(|(range1, range2)| range1.contains(range2) || range2.contains(range1))
-        .count();
+    let result = input.iter().filter(|group| group.any_contains()).count();
     println!("{:?}", result);
 }
 
 #[test]
 fn part2() {
     let input = parse(INPUT);
-    let result = input
-        .iter()
-        .filter(|(range1, range2)| range1.overlaps(range2))
-        .count();
+    let result = input.iter().filter(|group| group.all_overlap()).count();
     println!("{:?}", result);
 }
+
+#[test]
+fn sections_covered_by_more_than_k() {
+    let ranges = parse(INPUT)
+        .into_iter()
+        .flat_map(|g| g.0)
+        .collect::<Vec<_>>();
+    let total = ranges.iter().copied().collect::<IntervalSet>();
+    println!("{:?}", total.len());
+    println!("{:?}", covered_by_more_than(&ranges, 100));
+}
@@ -1,6 +1,7 @@
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 
 const INPUT: &str = include_str!("input.txt");
 
@@ -9,16 +10,16 @@ lazy_static! {
         Regex::new(r"move (?P<count>\d+) from (?P<from>\d+) to (?P<to>\d+)").unwrap();
 }
 
-#[derive(Debug)]
+#[derive(Debug, Eq, PartialEq)]
 struct Crate(char);
 
-#[derive(Debug)]
+#[derive(Debug, Eq, PartialEq)]
 struct Stack {
     id: usize,
     crates: Vec<Crate>,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
 struct Move {
     from: usize,
     to: usize,
@@ -83,104 +84,617 @@ fn parse(input: &str) -> (HashMap<usize, Stack>, Vec<Move>) {
         .map(Move::from)
         .collect::<Vec<Move>>();
 
-    stack_lines.reverse();
-    let stack_indices = stack_lines.remove(0);
-    let mut stacks = stack_indices
-        .split_whitespace()
-        .map(|s| Stack {
-            id: s
-                .trim()
-                .parse::<usize>()
-                .unwrap_or_else(|_| panic!("invalid value {:?}", s)),
+    let stacks = parse_diagram(&stack_lines).unwrap_or_else(|e| panic!("invalid diagram: {e:?}"));
+
+    (stacks, moves)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum DiagramError {
+    MissingIndexLine,
+    InvalidStackId(String),
+    DuplicateStackId(usize),
+    UnalignedCrate { line: usize, column: usize },
+}
+
+// Crates are matched to the stack whose id sits under their letter, so
+// trimmed lines and ids wider than one digit are fine.
+fn parse_diagram(lines: &[&str]) -> Result<HashMap<usize, Stack>, DiagramError> {
+    let (index_line, crate_lines) = lines.split_last().ok_or(DiagramError::MissingIndexLine)?;
+    let mut columns = Vec::new();
+    let chars = index_line.chars().collect::<Vec<_>>();
+    let mut col = 0;
+    while col < chars.len() {
+        if chars[col].is_whitespace() {
+            col += 1;
+            continue;
+        }
+        let start = col;
+        while col < chars.len() && !chars[col].is_whitespace() {
+            col += 1;
+        }
+        let token = chars[start..col].iter().collect::<String>();
+        let id = token
+            .parse::<usize>()
+            .map_err(|_| DiagramError::InvalidStackId(token))?;
+        columns.push((start..col, id));
+    }
+    let mut stacks = HashMap::new();
+    for (_, id) in &columns {
+        let stack = Stack {
+            id: *id,
             crates: Vec::new(),
-        })
-        .collect::<Vec<_>>();
-    for line in stack_lines {
-        for i in (0..line.len()).step_by(4) {
-            let crate_str = &line[i..i + 2];
-            if crate_str.starts_with('[') || crate_str.ends_with(']') {
-                stacks[i / 4]
-                    .crates
-                    .push(Crate(crate_str.chars().nth(1).expect("a char")));
+        };
+        if stacks.insert(*id, stack).is_some() {
+            return Err(DiagramError::DuplicateStackId(*id));
+        }
+    }
+    for (line, text) in crate_lines.iter().enumerate().rev() {
+        let chars = text.chars().collect::<Vec<_>>();
+        let mut col = 0;
+        while col < chars.len() {
+            if chars[col] != '[' {
+                col += 1;
+                continue;
             }
+            let unaligned = DiagramError::UnalignedCrate {
+                line: line + 1,
+                column: col,
+            };
+            if chars.get(col + 2) != Some(&']') {
+                return Err(unaligned);
+            }
+            let (_, id) = columns
+                .iter()
+                .find(|(span, _)| span.contains(&(col + 1)))
+                .ok_or(unaligned)?;
+            stacks
+                .get_mut(id)
+                .expect("a known stack")
+                .crates
+                .push(Crate(chars[col + 1]));
+            col += 3;
         }
     }
-    let stacks = stacks
-        .into_iter()
-        .map(|s| (s.id, s))
-        .collect::<HashMap<_, _>>();
+    Ok(stacks)
+}
 
-    (stacks, moves)
+fn render_diagram(stacks: &HashMap<usize, Stack>) -> String {
+    let mut stacks = stacks.values().collect::<Vec<&Stack>>();
+    stacks.sort_unstable_by_key(|s| s.id);
+    let height = stacks.iter().map(|s| s.crates.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for level in (0..height).rev() {
+        let line = stacks
+            .iter()
+            .map(|s| match s.crates.get(level) {
+                Some(c) => format!("[{}]", c.0),
+                None => "   ".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    let ids = stacks
+        .iter()
+        .map(|s| format!("{:^3}", s.id))
+        .collect::<Vec<_>>()
+        .join(" ");
+    out.push_str(ids.trim_end());
+    out.push('\n');
+    out
 }
 
-fn apply_moves(stacks: &mut HashMap<usize, Stack>, moves: &Vec<Move>) {
-    for m in moves {
-        for _ in 0..m.count {
-            let t = stacks
-                .get_mut(&m.from)
-                .expect("crate id exists")
-                .crates
-                .pop()
-                .expect("enough crates");
-            stacks
-                .get_mut(&m.to)
-                .expect("crate id exists")
-                .crates
-                .push(t);
+#[derive(Debug, Eq, PartialEq)]
+enum CraneError {
+    UnknownStack(usize),
+    NotEnoughCrates {
+        stack: usize,
+        available: usize,
+        requested: usize,
+    },
+}
+
+impl Display for CraneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CraneError::UnknownStack(id) => write!(f, "stack {id} does not exist"),
+            CraneError::NotEnoughCrates {
+                stack,
+                available,
+                requested,
+            } => write!(
+                f,
+                "stack {stack} has only {available} crates, {requested} requested"
+            ),
+        }
+    }
+}
+
+trait Crane {
+    // how many crates the next lift takes when `remaining` are left to move
+    fn lift_size(&self, remaining: usize) -> usize;
+
+    fn lifts(&self, count: usize) -> Vec<usize> {
+        let mut lifts = vec![];
+        let mut remaining = count;
+        while remaining > 0 {
+            let n = self.lift_size(remaining).clamp(1, remaining);
+            lifts.push(n);
+            remaining -= n;
+        }
+        lifts
+    }
+
+    fn one_at_a_time(&self, count: usize) -> bool {
+        self.lifts(count).iter().all(|n| *n == 1)
+    }
+
+    fn single_lift(&self, count: usize) -> bool {
+        self.lifts(count) == [count]
+    }
+}
+
+struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn lift_size(&self, _remaining: usize) -> usize {
+        1
+    }
+}
+
+struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn lift_size(&self, remaining: usize) -> usize {
+        remaining
+    }
+}
+
+struct LimitedCrane {
+    capacity: usize,
+}
+
+impl Crane for LimitedCrane {
+    fn lift_size(&self, remaining: usize) -> usize {
+        remaining.min(self.capacity)
+    }
+}
+
+fn lift(stacks: &mut HashMap<usize, Stack>, from: usize, to: usize, n: usize) {
+    let from = &mut stacks.get_mut(&from).expect("crate id exists").crates;
+    let crates = from.split_off(from.len() - n);
+    stacks
+        .get_mut(&to)
+        .expect("crate id exists")
+        .crates
+        .extend(crates);
+}
+
+struct Yard<C: Crane> {
+    stacks: HashMap<usize, Stack>,
+    crane: C,
+    history: Vec<Move>,
+}
+
+impl<C: Crane> Yard<C> {
+    fn new(stacks: HashMap<usize, Stack>, crane: C) -> Self {
+        Self {
+            stacks,
+            crane,
+            history: Vec::new(),
+        }
+    }
+
+    fn apply(&mut self, m: Move) -> Result<(), CraneError> {
+        if !self.stacks.contains_key(&m.to) {
+            return Err(CraneError::UnknownStack(m.to));
+        }
+        let available = self
+            .stacks
+            .get(&m.from)
+            .ok_or(CraneError::UnknownStack(m.from))?
+            .crates
+            .len();
+        if available < m.count {
+            return Err(CraneError::NotEnoughCrates {
+                stack: m.from,
+                available,
+                requested: m.count,
+            });
+        }
+        for n in self.crane.lifts(m.count) {
+            lift(&mut self.stacks, m.from, m.to, n);
         }
+        self.history.push(m);
+        Ok(())
+    }
+
+    fn apply_all(&mut self, moves: &[Move]) -> Result<(), CraneError> {
+        moves.iter().try_for_each(|m| self.apply(*m))
+    }
+
+    fn undo(&mut self) -> Option<Move> {
+        let m = self.history.pop()?;
+        for n in self.crane.lifts(m.count).into_iter().rev() {
+            lift(&mut self.stacks, m.to, m.from, n);
+        }
+        Some(m)
+    }
+
+    fn render(&self) -> String {
+        render_diagram(&self.stacks)
+    }
+
+    fn top_crates(&self) -> String {
+        let mut result = self.stacks.values().collect::<Vec<&Stack>>();
+        result.sort_unstable_by_key(|s| s.id);
+        result
+            .iter()
+            .filter_map(|s| s.crates.last())
+            .map(|c| c.0)
+            .collect()
     }
 }
 
-fn apply_moves_together(stacks: &mut HashMap<usize, Stack>, moves: &Vec<Move>) {
+// Merges consecutive moves between the same stacks, and cancels moves that
+// undo the previous one, as far as the crane model keeps that equivalent.
+fn compress<C: Crane>(crane: &C, moves: &[Move]) -> Vec<Move> {
+    let mut ret: Vec<Move> = Vec::with_capacity(moves.len());
     for m in moves {
-        let mut t = Vec::new();
-        for _ in 0..m.count {
-            t.push(
-                stacks
-                    .get_mut(&m.from)
-                    .expect("crate id exists")
-                    .crates
-                    .pop()
-                    .expect("enough crates"),
-            );
-        }
-        t.reverse();
-        stacks
-            .get_mut(&m.to)
-            .expect("crate id exists")
+        let mut m = *m;
+        if m.count == 0 || m.from == m.to {
+            continue;
+        }
+        while let Some(last) = ret.last_mut() {
+            if last.from == m.from && last.to == m.to && crane.one_at_a_time(last.count + m.count) {
+                last.count += m.count;
+                m.count = 0;
+            } else if last.from == m.to && last.to == m.from {
+                if crane.one_at_a_time(last.count.max(m.count)) {
+                    let cancelled = last.count.min(m.count);
+                    last.count -= cancelled;
+                    m.count -= cancelled;
+                } else if last.count == m.count && crane.single_lift(m.count) {
+                    last.count = 0;
+                    m.count = 0;
+                } else {
+                    break;
+                }
+                if last.count == 0 {
+                    ret.pop();
+                    continue;
+                }
+            }
+            break;
+        }
+        if m.count > 0 {
+            ret.push(m);
+        }
+    }
+    ret
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum PlanError {
+    DifferentStacks,
+    DifferentCrates,
+    TooFewStacks,
+}
+
+// Builds each target stack bottom up, one crate at a time. Single crate moves
+// behave the same under every crane model, the plan is then compressed for
+// the given one.
+fn plan<C: Crane>(
+    crane: &C,
+    start: &HashMap<usize, Stack>,
+    target: &HashMap<usize, Stack>,
+) -> Result<Vec<Move>, PlanError> {
+    let mut ids = start.keys().copied().collect::<Vec<_>>();
+    ids.sort_unstable();
+    let mut target_ids = target.keys().copied().collect::<Vec<_>>();
+    target_ids.sort_unstable();
+    if ids != target_ids {
+        return Err(PlanError::DifferentStacks);
+    }
+    let crates_of = |stacks: &HashMap<usize, Stack>| {
+        let mut crates = stacks
+            .values()
+            .flat_map(|s| s.crates.iter().map(|c| c.0))
+            .collect::<Vec<_>>();
+        crates.sort_unstable();
+        crates
+    };
+    if crates_of(start) != crates_of(target) {
+        return Err(PlanError::DifferentCrates);
+    }
+    let mut stacks = start
+        .iter()
+        .map(|(id, s)| (*id, s.crates.iter().map(|c| c.0).collect::<Vec<_>>()))
+        .collect::<HashMap<_, _>>();
+    let mut fixed = ids.iter().map(|id| (*id, 0)).collect::<HashMap<_, _>>();
+    let mut moves = vec![];
+    let mut move_one = |stacks: &mut HashMap<usize, Vec<char>>, from: usize, to: usize| {
+        let c = stacks
+            .get_mut(&from)
+            .expect("a stack")
+            .pop()
+            .expect("a crate");
+        stacks.get_mut(&to).expect("a stack").push(c);
+        moves.push(Move { from, to, count: 1 });
+    };
+    for t in &ids {
+        for (level, wanted) in target[t].crates.iter().enumerate() {
+            if stacks[t].get(level) == Some(&wanted.0) && stacks[t].len() == level + 1 {
+                fixed.insert(*t, level + 1);
+                continue;
+            }
+            while stacks[t].len() > level {
+                let buffer = *ids
+                    .iter()
+                    .find(|id| *id != t)
+                    .ok_or(PlanError::TooFewStacks)?;
+                move_one(&mut stacks, *t, buffer);
+            }
+            let (source, depth) = ids
+                .iter()
+                .filter(|id| *id != t)
+                .filter_map(|id| {
+                    let crates = &stacks[id];
+                    crates[fixed[id]..]
+                        .iter()
+                        .rposition(|c| *c == wanted.0)
+                        .map(|pos| (*id, crates.len() - fixed[id] - pos - 1))
+                })
+                .min_by_key(|(_, depth)| *depth)
+                .expect("crate multisets match");
+            if depth > 0 {
+                let buffer = *ids
+                    .iter()
+                    .find(|id| *id != t && **id != source)
+                    .ok_or(PlanError::TooFewStacks)?;
+                for _ in 0..depth {
+                    move_one(&mut stacks, source, buffer);
+                }
+            }
+            move_one(&mut stacks, source, *t);
+            fixed.insert(*t, level + 1);
+        }
+    }
+    Ok(compress(crane, &moves))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT_TEST: &str = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2";
+
+    #[test]
+    fn it_moves_with_each_crane() {
+        let (stacks, moves) = parse(INPUT_TEST);
+        let mut yard = Yard::new(stacks, CrateMover9000);
+        yard.apply_all(&moves).unwrap();
+        assert_eq!(yard.top_crates(), "CMZ");
+
+        let (stacks, moves) = parse(INPUT_TEST);
+        let mut yard = Yard::new(stacks, CrateMover9001);
+        yard.apply_all(&moves).unwrap();
+        assert_eq!(yard.top_crates(), "MCD");
+
+        let (stacks, moves) = parse(INPUT_TEST);
+        let mut yard = Yard::new(stacks, LimitedCrane { capacity: 2 });
+        yard.apply_all(&moves).unwrap();
+        assert_eq!(LimitedCrane { capacity: 2 }.lifts(5), vec![2, 2, 1]);
+        assert_eq!(yard.top_crates(), "MCZ");
+        let crates = yard.stacks[&3]
             .crates
-            .extend(t);
+            .iter()
+            .map(|c| c.0)
+            .collect::<String>();
+        assert_eq!(crates, "PNDZ");
+    }
+
+    fn round_trip(diagram: &str) -> String {
+        let lines = diagram.lines().collect::<Vec<_>>();
+        let stacks = parse_diagram(&lines).unwrap();
+        let rendered = render_diagram(&stacks);
+        let lines = rendered.lines().collect::<Vec<_>>();
+        assert_eq!(parse_diagram(&lines).unwrap(), stacks);
+        rendered
+    }
+
+    #[test]
+    fn it_round_trips_diagrams() {
+        let diagram = INPUT_TEST.split("\n\n").next().unwrap();
+        let rendered = round_trip(diagram);
+        assert_eq!(rendered, "    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3\n");
+        assert_eq!(round_trip(&rendered), rendered);
+
+        let diagram = INPUT.split("\n\n").next().unwrap();
+        assert_eq!(round_trip(diagram).lines().count(), diagram.lines().count());
+
+        let wide = "[A]                                     [L]
+[B] [C]                                 [M] [N]
+ 1   2   3   4   5   6   7   8   9  10  11  12";
+        let rendered = round_trip(wide);
+        assert_eq!(rendered, format!("{wide}\n"));
+        let stacks = parse_diagram(&wide.lines().collect::<Vec<_>>()).unwrap();
+        assert_eq!(stacks[&11].crates, vec![Crate('M'), Crate('L')]);
+        assert_eq!(stacks[&12].crates, vec![Crate('N')]);
+    }
+
+    #[test]
+    fn it_renders_after_moves() {
+        let (stacks, moves) = parse(INPUT_TEST);
+        let mut yard = Yard::new(stacks, CrateMover9000);
+        yard.apply_all(&moves[..2]).unwrap();
+        assert_eq!(
+            yard.render(),
+            "        [Z]\n        [N]\n    [C] [D]\n    [M] [P]\n 1   2   3\n"
+        );
+    }
+
+    #[test]
+    fn it_rejects_bad_diagrams() {
+        assert_eq!(parse_diagram(&[]), Err(DiagramError::MissingIndexLine));
+        assert_eq!(
+            parse_diagram(&["[A]", " a"]),
+            Err(DiagramError::InvalidStackId("a".to_string()))
+        );
+        assert_eq!(
+            parse_diagram(&["[A]", " 1   1"]),
+            Err(DiagramError::DuplicateStackId(1))
+        );
+        assert_eq!(
+            parse_diagram(&["[A] [B]", " 1"]),
+            Err(DiagramError::UnalignedCrate { line: 1, column: 4 })
+        );
+        assert_eq!(
+            parse_diagram(&["[A", " 1"]),
+            Err(DiagramError::UnalignedCrate { line: 1, column: 0 })
+        );
+    }
+
+    fn moves(text: &str) -> Vec<Move> {
+        text.lines().map(Move::from).collect()
+    }
+
+    #[test]
+    fn it_compresses_moves() {
+        let list = moves(
+            "move 1 from 1 to 2\nmove 2 from 1 to 2\nmove 1 from 2 to 1\nmove 2 from 3 to 3\nmove 1 from 2 to 3",
+        );
+        assert_eq!(
+            compress(&CrateMover9000, &list),
+            moves("move 2 from 1 to 2\nmove 1 from 2 to 3")
+        );
+        assert_eq!(
+            compress(&CrateMover9001, &list),
+            moves("move 1 from 1 to 2\nmove 2 from 1 to 2\nmove 1 from 2 to 1\nmove 1 from 2 to 3")
+        );
+        assert_eq!(
+            compress(
+                &CrateMover9001,
+                &moves("move 2 from 1 to 2\nmove 2 from 2 to 1")
+            ),
+            vec![]
+        );
+        assert_eq!(
+            compress(
+                &LimitedCrane { capacity: 2 },
+                &moves("move 3 from 1 to 2\nmove 3 from 2 to 1")
+            )
+            .len(),
+            2
+        );
+        let (stacks, list) = parse(INPUT);
+        let compressed = compress(&CrateMover9000, &list);
+        let mut yard = Yard::new(stacks, CrateMover9000);
+        yard.apply_all(&compressed).unwrap();
+        let (stacks, _) = parse(INPUT);
+        let mut reference = Yard::new(stacks, CrateMover9000);
+        reference.apply_all(&list).unwrap();
+        assert_eq!(yard.render(), reference.render());
+    }
+
+    fn assert_plan_reaches<C: Crane>(crane: C, start: &str, target: &str) -> usize {
+        let diagram = |d: &str| parse_diagram(&d.lines().collect::<Vec<_>>()).unwrap();
+        let plan = plan(&crane, &diagram(start), &diagram(target)).unwrap();
+        let mut yard = Yard::new(diagram(start), crane);
+        yard.apply_all(&plan).unwrap();
+        assert_eq!(yard.stacks, diagram(target));
+        plan.len()
+    }
+
+    #[test]
+    fn it_plans_moves() {
+        let start = INPUT_TEST.split("\n\n").next().unwrap();
+        let target = "        [Z]\n        [N]\n        [D]\n[C]     [P]\n[M]     [P]\n 1   2   3";
+        assert_eq!(
+            plan(
+                &CrateMover9000,
+                &parse(INPUT_TEST).0,
+                &parse_diagram(&target.lines().collect::<Vec<_>>()).unwrap()
+            ),
+            Err(PlanError::DifferentCrates)
+        );
+        let target = "        [Z]\n        [N]\n[C]     [D]\n[M]     [P]\n 1   2   3";
+        assert_plan_reaches(CrateMover9000, start, target);
+        assert_plan_reaches(CrateMover9001, start, target);
+        assert_plan_reaches(LimitedCrane { capacity: 2 }, start, target);
+        assert_eq!(assert_plan_reaches(CrateMover9000, start, start), 0);
+
+        let start = INPUT.split("\n\n").next().unwrap();
+        let (stacks, list) = parse(INPUT);
+        let mut yard = Yard::new(stacks, CrateMover9001);
+        yard.apply_all(&list).unwrap();
+        let target = yard.render();
+        assert_plan_reaches(CrateMover9000, start, &target);
+        assert_plan_reaches(CrateMover9001, start, &target);
+    }
+
+    #[test]
+    fn it_reports_errors() {
+        let (stacks, _) = parse(INPUT_TEST);
+        let mut yard = Yard::new(stacks, CrateMover9000);
+        let err = yard
+            .apply(Move {
+                from: 3,
+                to: 1,
+                count: 2,
+            })
+            .unwrap_err();
+        assert_eq!(err.to_string(), "stack 3 has only 1 crates, 2 requested");
+        assert_eq!(
+            yard.apply(Move {
+                from: 4,
+                to: 1,
+                count: 1
+            }),
+            Err(CraneError::UnknownStack(4))
+        );
+        assert!(yard.history.is_empty());
+    }
+
+    #[test]
+    fn it_undoes_moves() {
+        for capacity in 1..4 {
+            let (stacks, moves) = parse(INPUT_TEST);
+            let mut yard = Yard::new(stacks, LimitedCrane { capacity });
+            yard.apply_all(&moves).unwrap();
+            let mut undone = vec![];
+            while let Some(m) = yard.undo() {
+                undone.push(m);
+            }
+            undone.reverse();
+            assert_eq!(undone, moves);
+            assert_eq!(yard.top_crates(), "NDP");
+        }
     }
 }
 
 #[test]
 fn part1() {
-    let (mut stacks, moves) = parse(INPUT);
-    apply_moves(&mut stacks, &moves);
-    let mut result = stacks
-        .iter()
-        .map(|(_, stack)| stack)
-        .collect::<Vec<&Stack>>();
-    result.sort_unstable_by(|a, b| a.id.cmp(&b.id));
-    let result = result
-        .iter()
-        .map(|s| s.crates.last().expect("at least a crate per stack").0)
-        .collect::<String>();
-    println!("{:?}", result);
+    let (stacks, moves) = parse(INPUT);
+    let mut yard = Yard::new(stacks, CrateMover9000);
+    yard.apply_all(&moves).expect("valid moves");
+    println!("{:?}", yard.top_crates());
 }
 
 #[test]
 fn part2() {
-    let (mut stacks, moves) = parse(INPUT);
-    apply_moves_together(&mut stacks, &moves);
-    let mut result = stacks
-        .iter()
-        .map(|(_, stack)| stack)
-        .collect::<Vec<&Stack>>();
-    result.sort_unstable_by(|a, b| a.id.cmp(&b.id));
-    let result = result
-        .iter()
-        .map(|s| s.crates.last().expect("at least a crate per stack").0)
-        .collect::<String>();
-    println!("{:?}", result);
+    let (stacks, moves) = parse(INPUT);
+    let mut yard = Yard::new(stacks, CrateMover9001);
+    yard.apply_all(&moves).expect("valid moves");
+    println!("{:?}", yard.top_crates());
 }
@@ -1,20 +1,393 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
 
 const INPUT: &str = include_str!("input.txt");
 
-fn find_marker(input: &str, n_distinct_char: usize) -> usize {
-    for i in n_distinct_char..input.len() {
-        let marker = &input[i - n_distinct_char..i];
-        let mut uniq = HashSet::new();
-        if marker.chars().all(|c| uniq.insert(c)) {
-            return i;
+// Sliding window over a stream of chars. Instead of rescanning the window,
+// it remembers where each char was last seen and moves the window start past
+// a repeated char in one jump.
+struct Detector {
+    window: usize,
+    start: usize,
+    pos: usize,
+    ascii: [Option<usize>; 128],
+    other: HashMap<char, usize>,
+}
+
+impl Detector {
+    fn new(window: usize) -> Self {
+        Detector {
+            window,
+            start: 0,
+            pos: 0,
+            ascii: [None; 128],
+            other: HashMap::new(),
+        }
+    }
+
+    // Number of chars pushed so far
+    fn position(&self) -> usize {
+        self.pos
+    }
+
+    // Pushes the next char, returns true when the last `window` chars are all different
+    fn push(&mut self, c: char) -> bool {
+        let last = if c.is_ascii() {
+            self.ascii[c as usize].replace(self.pos)
+        } else {
+            self.other.insert(c, self.pos)
+        };
+        if let Some(last) = last {
+            self.start = self.start.max(last + 1);
+        }
+        self.pos += 1;
+        self.pos - self.start >= self.window
+    }
+
+    // Forgets the current window, so the next marker only uses chars pushed after this point
+    fn restart(&mut self) {
+        self.start = self.pos;
+    }
+}
+
+// Decodes UTF-8 chars from a reader one at a time, without loading it whole.
+struct Utf8Chars<R: Read> {
+    reader: BufReader<R>,
+}
+
+impl<R: Read> Utf8Chars<R> {
+    fn new(reader: R) -> Self {
+        Utf8Chars {
+            reader: BufReader::new(reader),
+        }
+    }
+
+    fn invalid() -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, "stream is not valid UTF-8")
+    }
+
+    fn read_char(&mut self) -> io::Result<Option<char>> {
+        let mut buf = [0u8; 4];
+        if self.reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        self.reader.read_exact(&mut buf[..1])?;
+        let len = match buf[0] {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Err(Self::invalid()),
+        };
+        self.reader
+            .read_exact(&mut buf[1..len])
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::UnexpectedEof => Self::invalid(),
+                _ => e,
+            })?;
+        let s = std::str::from_utf8(&buf[..len]).map_err(|_| Self::invalid())?;
+        Ok(s.chars().next())
+    }
+}
+
+impl<R: Read> Iterator for Utf8Chars<R> {
+    type Item = io::Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_char().transpose()
+    }
+}
+
+// Position (in chars) right after the first window of `n_distinct_char` different chars
+fn find_marker_in<I: IntoIterator<Item = char>>(chars: I, n_distinct_char: usize) -> Option<usize> {
+    let mut detector = Detector::new(n_distinct_char);
+    if n_distinct_char == 0 {
+        return Some(0);
+    }
+    chars
+        .into_iter()
+        .find(|c| detector.push(*c))
+        .map(|_| detector.position())
+}
+
+fn read_marker<R: Read>(reader: R, n_distinct_char: usize) -> io::Result<Option<usize>> {
+    let mut detector = Detector::new(n_distinct_char);
+    if n_distinct_char == 0 {
+        return Ok(Some(0));
+    }
+    for c in Utf8Chars::new(reader) {
+        if detector.push(c?) {
+            return Ok(Some(detector.position()));
+        }
+    }
+    Ok(None)
+}
+
+fn find_marker(input: &str, n_distinct_char: usize) -> Option<usize> {
+    find_marker_in(input.chars(), n_distinct_char)
+}
+
+const START_OF_PACKET: usize = 4;
+const START_OF_MESSAGE: usize = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Marker {
+    window: usize,
+    // position (in chars) right after the marker
+    position: usize,
+}
+
+#[derive(Debug)]
+enum ScanError {
+    EmptyWindow,
+    Io(io::Error),
+}
+
+impl From<io::Error> for ScanError {
+    fn from(e: io::Error) -> Self {
+        ScanError::Io(e)
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::EmptyWindow => write!(f, "window size must be at least 1"),
+            ScanError::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+// Every marker of every window size. After a marker, the next one of the same
+// size only starts counting from the following char, so markers never overlap.
+#[derive(Debug)]
+struct Scan {
+    windows: Vec<usize>,
+    markers: Vec<Marker>,
+    length: usize,
+}
+
+impl Scan {
+    fn positions(&self, window: usize) -> Vec<usize> {
+        self.markers
+            .iter()
+            .filter(|m| m.window == window)
+            .map(|m| m.position)
+            .collect()
+    }
+
+    fn first(&self, window: usize) -> Option<usize> {
+        self.markers
+            .iter()
+            .find(|m| m.window == window)
+            .map(|m| m.position)
+    }
+}
+
+impl fmt::Display for Scan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} chars", self.length)?;
+        for window in &self.windows {
+            let positions = self.positions(*window);
+            write!(f, "window {window}: {} markers", positions.len())?;
+            if let Some(first) = positions.first() {
+                write!(f, ", first at {first}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+struct Scanner {
+    detectors: Vec<Detector>,
+    markers: Vec<Marker>,
+}
+
+impl Scanner {
+    fn new(windows: &[usize]) -> Result<Self, ScanError> {
+        if windows.contains(&0) {
+            return Err(ScanError::EmptyWindow);
         }
+        let mut windows = windows.to_vec();
+        windows.sort_unstable();
+        windows.dedup();
+        Ok(Scanner {
+            detectors: windows.into_iter().map(Detector::new).collect(),
+            markers: vec![],
+        })
     }
-    panic!("No marker found")
+
+    fn push(&mut self, c: char) {
+        for detector in self.detectors.iter_mut() {
+            if detector.push(c) {
+                self.markers.push(Marker {
+                    window: detector.window,
+                    position: detector.position(),
+                });
+                detector.restart();
+            }
+        }
+    }
+
+    // Windows without any marker are kept, with no positions
+    fn finish(self) -> Scan {
+        let windows = self.detectors.iter().map(|d| d.window).collect::<Vec<_>>();
+        Scan {
+            windows,
+            markers: self.markers,
+            length: self.detectors.first().map_or(0, |d| d.position()),
+        }
+    }
+}
+
+fn scan<I: IntoIterator<Item = char>>(chars: I, windows: &[usize]) -> Result<Scan, ScanError> {
+    let mut scanner = Scanner::new(windows)?;
+    chars.into_iter().for_each(|c| scanner.push(c));
+    Ok(scanner.finish())
+}
+
+fn scan_reader<R: Read>(reader: R, windows: &[usize]) -> Result<Scan, ScanError> {
+    let mut scanner = Scanner::new(windows)?;
+    for c in Utf8Chars::new(reader) {
+        scanner.push(c?);
+    }
+    Ok(scanner.finish())
+}
+
+// The messages following each marker of the given size, up to the next marker
+fn segments(input: &str, window: usize) -> Result<Vec<&str>, ScanError> {
+    let scan = scan(input.chars(), &[window])?;
+    let mut offsets = input.char_indices().map(|(i, _)| i).collect::<Vec<_>>();
+    offsets.push(input.len());
+    let positions = scan.positions(window);
+    let ends = positions
+        .iter()
+        .skip(1)
+        .map(|p| p - window)
+        .chain([scan.length]);
+    Ok(positions
+        .iter()
+        .zip(ends)
+        .map(|(start, end)| &input[offsets[*start]..offsets[end]])
+        .collect())
 }
 
 #[test]
 fn part1() {
-    let result = find_marker(INPUT, 14);
+    let result = find_marker(INPUT, START_OF_PACKET);
     println!("{:?}", result);
 }
+
+#[test]
+fn part2() {
+    let result = find_marker(INPUT, START_OF_MESSAGE);
+    println!("{:?}", result);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLES: [(&str, usize, usize); 5] = [
+        ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+        ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+        ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+        ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+        ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+    ];
+
+    #[test]
+    fn it_finds_markers() {
+        for (input, packet, message) in EXAMPLES {
+            assert_eq!(find_marker(input, 4), Some(packet));
+            assert_eq!(find_marker(input, 14), Some(message));
+            assert_eq!(read_marker(input.as_bytes(), 4).unwrap(), Some(packet));
+            assert_eq!(read_marker(input.as_bytes(), 14).unwrap(), Some(message));
+        }
+        assert_eq!(find_marker(INPUT, 14), Some(2746));
+        assert_eq!(read_marker(INPUT.as_bytes(), 14).unwrap(), Some(2746));
+        assert_eq!(find_marker_in("aaaa".chars(), 2), None);
+        assert_eq!(find_marker_in("abc".chars(), 4), None);
+    }
+
+    #[test]
+    fn it_counts_chars_not_bytes() {
+        assert_eq!(find_marker("ééàéùç", 3), Some(5));
+        assert_eq!(read_marker("ééàéùç".as_bytes(), 3).unwrap(), Some(5));
+        assert_eq!(read_marker("a🦀b🦀c".as_bytes(), 3).unwrap(), Some(3));
+    }
+
+    #[test]
+    fn it_rejects_invalid_utf8() {
+        let err = read_marker(&[b'a', 0xff, b'b'][..], 3).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = read_marker(&[b'a', 0xc3][..], 3).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn it_streams_long_inputs() {
+        let repeated = std::iter::repeat_n("abcab", 200_000).flat_map(str::chars);
+        assert_eq!(
+            find_marker_in(repeated.clone().chain("xyz".chars()), 4),
+            Some(1_000_001)
+        );
+        assert_eq!(find_marker_in(repeated, 4), None);
+    }
+
+    #[test]
+    fn it_scans_every_marker() {
+        let report = scan("abcabcaabbcdd".chars(), &[3, 2]).unwrap();
+        assert_eq!(report.positions(3), vec![3, 6, 12]);
+        assert_eq!(report.positions(2), vec![2, 4, 6, 9, 11]);
+        assert_eq!(report.first(3), Some(3));
+        assert_eq!(report.length, 13);
+        assert_eq!(
+            report.to_string(),
+            "13 chars\nwindow 2: 5 markers, first at 2\nwindow 3: 3 markers, first at 3\n"
+        );
+
+        for (input, packet, message) in EXAMPLES {
+            let report =
+                scan_reader(input.as_bytes(), &[START_OF_PACKET, START_OF_MESSAGE]).unwrap();
+            assert_eq!(report.first(START_OF_PACKET), Some(packet));
+            assert_eq!(report.first(START_OF_MESSAGE), Some(message));
+        }
+        let report = scan(INPUT.chars(), &[START_OF_PACKET, START_OF_MESSAGE]).unwrap();
+        assert_eq!(report.first(START_OF_PACKET), Some(1480));
+        assert_eq!(report.first(START_OF_MESSAGE), Some(2746));
+    }
+
+    #[test]
+    fn it_segments_messages() {
+        assert_eq!(
+            segments("abcdaabbabbaefghxxyxx", 4).unwrap(),
+            vec!["aabbab", "ghxxyxx"]
+        );
+        assert_eq!(segments("éàùaabba", 3).unwrap(), vec!["aabba"]);
+    }
+
+    #[test]
+    fn it_reports_missing_markers() {
+        assert_eq!(find_marker("aaaa", 2), None);
+        let report = scan("abcdaaaa".chars(), &[4, 5]).unwrap();
+        assert_eq!(report.positions(4), vec![4]);
+        assert_eq!(report.first(5), None);
+        assert_eq!(
+            report.to_string(),
+            "8 chars\nwindow 4: 1 markers, first at 4\nwindow 5: 0 markers\n"
+        );
+        assert!(matches!(
+            scan("abcd".chars(), &[0]),
+            Err(ScanError::EmptyWindow)
+        ));
+        assert!(segments("", 1).unwrap().is_empty());
+        assert!(segments("aaaa", 2).unwrap().is_empty());
+        assert!(matches!(
+            scan_reader(&[0xff][..], &[1]),
+            Err(ScanError::Io(e)) if e.kind() == io::ErrorKind::InvalidData
+        ));
+    }
+}
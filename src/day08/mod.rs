@@ -1,7 +1,10 @@
 use std::collections::HashSet;
+use std::fmt::Write;
 
 const INPUT: &str = include_str!("input.txt");
 
+type Tree = (usize, usize);
+
 struct Grid(Vec<Vec<usize>>);
 
 fn parse(input: &str) -> Grid {
@@ -81,6 +84,261 @@ impl Grid {
     }
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Compass {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Compass {
+    const CARDINAL: [Compass; 4] = [Compass::North, Compass::South, Compass::West, Compass::East];
+    const ALL: [Compass; 8] = [
+        Compass::North,
+        Compass::NorthEast,
+        Compass::East,
+        Compass::SouthEast,
+        Compass::South,
+        Compass::SouthWest,
+        Compass::West,
+        Compass::NorthWest,
+    ];
+
+    fn delta(&self) -> (isize, isize) {
+        match self {
+            Compass::North => (-1, 0),
+            Compass::NorthEast => (-1, 1),
+            Compass::East => (0, 1),
+            Compass::SouthEast => (1, 1),
+            Compass::South => (1, 0),
+            Compass::SouthWest => (1, -1),
+            Compass::West => (0, -1),
+            Compass::NorthWest => (-1, -1),
+        }
+    }
+}
+
+impl Grid {
+    fn contains(&self, (row, col): (isize, isize)) -> bool {
+        row >= 0 && col >= 0 && row < self.rows() as isize && col < self.columns() as isize
+    }
+
+    // Steps along `delta` until the ray from `from` first lands in the grid,
+    // `None` when it never does.
+    fn steps_to_enter(&self, from: (isize, isize), delta: (isize, isize)) -> Option<i128> {
+        let (mut first, mut last) = (1, i128::MAX);
+        for (c, d, n) in [
+            (from.0, delta.0, self.rows()),
+            (from.1, delta.1, self.columns()),
+        ] {
+            let (c, n) = (c as i128, n as i128);
+            let (lo, hi) = match d.signum() {
+                0 if (0..n).contains(&c) => continue,
+                0 => return None,
+                1 => (-c, n - 1 - c),
+                _ => (c - (n - 1), c),
+            };
+            (first, last) = (first.max(lo), last.min(hi));
+        }
+        (first <= last).then_some(first)
+    }
+
+    // `from` may lie outside the grid, the ray then starts at the first tree
+    // it meets on the forest edge.
+    fn line_of_sight(&self, from: (isize, isize), height: usize, direction: Compass) -> Vec<Tree> {
+        let (d_row, d_col) = direction.delta();
+        let mut ret = vec![];
+        let Some(steps) = self.steps_to_enter(from, (d_row, d_col)) else {
+            return ret;
+        };
+        // in the grid, so both fit back into an isize
+        let mut row = (from.0 as i128 + steps * d_row as i128) as isize;
+        let mut col = (from.1 as i128 + steps * d_col as i128) as isize;
+        while self.contains((row, col)) {
+            let tree = (row as usize, col as usize);
+            ret.push(tree);
+            if self.0[tree.0][tree.1] >= height {
+                break;
+            }
+            row += d_row;
+            col += d_col;
+        }
+        ret
+    }
+
+    fn viewing_distance(&self, from: (isize, isize), height: usize, direction: Compass) -> usize {
+        self.line_of_sight(from, height, direction).len()
+    }
+
+    fn trees_seen_from(&self, from: (isize, isize), height: usize) -> HashSet<Tree> {
+        Compass::ALL
+            .into_iter()
+            .flat_map(|direction| self.line_of_sight(from, height, direction))
+            .collect()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Style {
+    Plain,
+    Ansi,
+}
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_VISIBLE: &str = "\x1b[32m";
+const ANSI_HIDDEN: &str = "\x1b[90m";
+const ANSI_SIGHT_LINE: &str = "\x1b[30;43m";
+const ANSI_BEST: &str = "\x1b[1;37;41m";
+
+impl Grid {
+    fn scenic_scores(&self) -> Vec<Vec<usize>> {
+        (0..self.rows())
+            .map(|row| {
+                (0..self.columns())
+                    .map(|col| self.scenic_score((row, col)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn best_tree(&self) -> Option<((usize, usize), usize)> {
+        let mut best = None;
+        for (row, scores) in self.scenic_scores().into_iter().enumerate() {
+            for (col, score) in scores.into_iter().enumerate() {
+                if best.is_none_or(|(_, best_score)| score > best_score) {
+                    best = Some(((row, col), score));
+                }
+            }
+        }
+        best
+    }
+
+    fn sight_line(&self, (row, col): Tree, direction: Compass) -> Vec<Tree> {
+        self.line_of_sight((row as isize, col as isize), self.0[row][col], direction)
+    }
+
+    fn sight_lines(&self, tree: Tree) -> HashSet<Tree> {
+        Compass::CARDINAL
+            .into_iter()
+            .flat_map(|direction| self.sight_line(tree, direction))
+            .collect()
+    }
+
+    fn highlights(&self) -> (Option<Tree>, HashSet<Tree>) {
+        match self.best_tree() {
+            Some((tree, _)) => (Some(tree), self.sight_lines(tree)),
+            None => (None, HashSet::new()),
+        }
+    }
+
+    fn visibility_map(&self, style: Style) -> String {
+        let visible = self.visible_trees();
+        let (best, sight_lines) = self.highlights();
+        let mut out = String::new();
+        for row in 0..self.rows() {
+            for col in 0..self.columns() {
+                let tree = (row, col);
+                let height = self.0[row][col];
+                match style {
+                    Style::Plain => {
+                        let c = if best == Some(tree) {
+                            '@'
+                        } else if sight_lines.contains(&tree) {
+                            '*'
+                        } else if visible.contains(&tree) {
+                            char::from_digit(height as u32, 10).expect("a digit")
+                        } else {
+                            '.'
+                        };
+                        out.push(c);
+                    }
+                    Style::Ansi => {
+                        let color = if best == Some(tree) {
+                            ANSI_BEST
+                        } else if sight_lines.contains(&tree) {
+                            ANSI_SIGHT_LINE
+                        } else if visible.contains(&tree) {
+                            ANSI_VISIBLE
+                        } else {
+                            ANSI_HIDDEN
+                        };
+                        write!(out, "{color}{height}{ANSI_RESET}").unwrap();
+                    }
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn heat_colors(&self) -> Vec<Vec<(u8, u8, u8)>> {
+        let scores = self.scenic_scores();
+        let max = scores.iter().flatten().copied().max().unwrap_or(0).max(1);
+        let (best, sight_lines) = self.highlights();
+        scores
+            .iter()
+            .enumerate()
+            .map(|(row, scores)| {
+                scores
+                    .iter()
+                    .enumerate()
+                    .map(|(col, &score)| {
+                        if best == Some((row, col)) {
+                            return (255, 255, 255);
+                        }
+                        let heat = (score * 255 / max) as u8;
+                        let green = if sight_lines.contains(&(row, col)) {
+                            255
+                        } else {
+                            0
+                        };
+                        (heat, green, 255 - heat)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn heatmap_ppm(&self) -> String {
+        let mut out = format!("P3\n{} {}\n255\n", self.columns(), self.rows());
+        for row in self.heat_colors() {
+            let line = row
+                .iter()
+                .map(|(r, g, b)| format!("{r} {g} {b}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(out, "{line}").unwrap();
+        }
+        out
+    }
+
+    fn heatmap_svg(&self, cell_size: usize) -> String {
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+            self.columns() * cell_size,
+            self.rows() * cell_size
+        );
+        for (row, colors) in self.heat_colors().into_iter().enumerate() {
+            for (col, (r, g, b)) in colors.into_iter().enumerate() {
+                writeln!(
+                    out,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{cell_size}\" height=\"{cell_size}\" fill=\"rgb({r},{g},{b})\"/>",
+                    col * cell_size,
+                    row * cell_size
+                )
+                .unwrap();
+            }
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
 fn trees_lower_than_first(heights: &[usize]) -> usize {
     let mut ret = 0;
     let mut idx = 1;
@@ -111,7 +369,7 @@ fn visible_trees_in_vec(heights: &[usize]) -> Vec<usize> {
 
 #[cfg(test)]
 mod tests {
-    use crate::day08::{parse, visible_trees_in_vec};
+    use crate::day08::{parse, visible_trees_in_vec, Compass, Style, ANSI_BEST, ANSI_SIGHT_LINE};
     use std::collections::HashSet;
 
     const INPUT: &str = r"30373
@@ -180,6 +438,103 @@ mod tests {
         assert_eq!(grid.scenic_score((1, 2)), 4);
         assert_eq!(grid.scenic_score((3, 2)), 8);
     }
+
+    #[test]
+    fn it_finds_best_tree_and_sight_lines() {
+        let grid = parse(INPUT);
+        assert_eq!(grid.best_tree(), Some(((3, 2), 8)));
+        assert_eq!(
+            grid.sight_lines((3, 2)),
+            vec![(2, 2), (1, 2), (4, 2), (3, 1), (3, 0), (3, 3), (3, 4)]
+                .into_iter()
+                .collect::<HashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn it_looks_in_any_direction() {
+        let grid = parse(INPUT);
+        assert_eq!(grid.viewing_distance((3, 2), 5, Compass::North), 2);
+        assert_eq!(grid.viewing_distance((3, 2), 5, Compass::NorthEast), 2);
+        assert_eq!(grid.viewing_distance((3, 2), 5, Compass::NorthWest), 1);
+        assert_eq!(grid.viewing_distance((3, 2), 5, Compass::SouthEast), 1);
+        assert_eq!(grid.viewing_distance((0, 0), 3, Compass::North), 0);
+        assert_eq!(grid.viewing_distance((2, 2), 9, Compass::East), 2);
+    }
+
+    #[test]
+    fn it_looks_in_from_outside() {
+        let grid = parse(INPUT);
+        assert_eq!(
+            grid.line_of_sight((2, -3), 7, Compass::East),
+            vec![(2, 0), (2, 1), (2, 2), (2, 3), (2, 4)]
+        );
+        assert_eq!(
+            grid.line_of_sight((-1, -1), 4, Compass::SouthEast),
+            vec![(0, 0), (1, 1)]
+        );
+        assert!(grid
+            .line_of_sight((-1, -1), 4, Compass::NorthEast)
+            .is_empty());
+        assert_eq!(
+            grid.line_of_sight((2, -1_000_000_000_000), 7, Compass::East),
+            vec![(2, 0), (2, 1), (2, 2), (2, 3), (2, 4)]
+        );
+        assert_eq!(
+            grid.line_of_sight(
+                (-1_000_000_000_000, -1_000_000_000_000),
+                9,
+                Compass::SouthEast
+            ),
+            vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)]
+        );
+        assert!(grid
+            .line_of_sight(
+                (-1_000_000_000_000, -999_999_999_990),
+                9,
+                Compass::SouthEast
+            )
+            .is_empty());
+        assert!(grid
+            .line_of_sight((isize::MAX, 2), 9, Compass::South)
+            .is_empty());
+        assert_eq!(
+            grid.line_of_sight((isize::MAX, 2), 9, Compass::North).len(),
+            5
+        );
+        assert!(grid
+            .line_of_sight((isize::MIN, isize::MAX), 9, Compass::SouthWest)
+            .is_empty());
+        assert_eq!(
+            grid.trees_seen_from((-1, 2), 0),
+            vec![(0, 1), (0, 2), (0, 3)]
+                .into_iter()
+                .collect::<HashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn it_renders_visibility_map() {
+        let grid = parse(INPUT);
+        assert_eq!(
+            grid.visibility_map(Style::Plain),
+            "30373\n25*.2\n65*32\n**@**\n35*90\n"
+        );
+        let ansi = grid.visibility_map(Style::Ansi);
+        assert_eq!(ansi.matches(ANSI_BEST).count(), 1);
+        assert_eq!(ansi.matches(ANSI_SIGHT_LINE).count(), 7);
+    }
+
+    #[test]
+    fn it_renders_heatmap() {
+        let grid = parse(INPUT);
+        let ppm = grid.heatmap_ppm();
+        assert!(ppm.starts_with("P3\n5 5\n255\n"));
+        assert_eq!(ppm.lines().count(), 3 + 5);
+        let svg = grid.heatmap_svg(10);
+        assert_eq!(svg.matches("<rect").count(), 25);
+        assert_eq!(svg.matches("rgb(255,255,255)").count(), 1);
+    }
 }
 
 #[test]
@@ -1,7 +1,9 @@
 use crate::day09::Direction::{Down, Left, Right, Up};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::{Display, Formatter};
+use std::io::Write;
 use std::str::FromStr;
+use std::time::Duration;
 
 const INPUT: &str = include_str!("input.txt");
 
@@ -19,84 +21,435 @@ struct Point {
     y: i32,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum FollowRule {
+    Chebyshev,
+    FourConnected,
+    Slack(i32),
+}
+
+impl FollowRule {
+    fn follow(&self, leader: Point, follower: Point) -> Point {
+        let dx = leader.x - follower.x;
+        let dy = leader.y - follower.y;
+        match self {
+            FollowRule::Chebyshev => FollowRule::Slack(1).follow(leader, follower),
+            FollowRule::Slack(slack) => {
+                if dx.abs() > *slack || dy.abs() > *slack {
+                    Point {
+                        x: follower.x + dx.signum(),
+                        y: follower.y + dy.signum(),
+                    }
+                } else {
+                    follower
+                }
+            }
+            FollowRule::FourConnected => {
+                if dx.abs() + dy.abs() <= 1 {
+                    follower
+                } else if dx.abs() > dy.abs() {
+                    Point {
+                        x: follower.x + dx.signum(),
+                        y: follower.y,
+                    }
+                } else {
+                    Point {
+                        x: follower.x,
+                        y: follower.y + dy.signum(),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
 struct Rope {
-    head: Point,
-    tail: Point,
+    knots: Vec<Point>,
+    rule: FollowRule,
+    visited: Vec<Visited>,
 }
 
-#[derive(Debug, Default)]
-struct NewRope {
-    pieces: [Rope; 10],
+impl Default for Rope {
+    fn default() -> Self {
+        Rope::new(2, FollowRule::Chebyshev)
+    }
 }
 
-impl Display for NewRope {
+impl Display for Rope {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut grid = [['-'; 20]; 20];
-        grid[(self.pieces.first().unwrap().head.x + 10) as usize]
-            [(self.pieces.first().unwrap().head.y + 10) as usize] = 'H';
-        for (i, p) in self.pieces.iter().enumerate().skip(1) {
-            let Point { x, y } = p.head;
-            grid[(x + 10) as usize][(y + 10) as usize] = char::from_digit(i as u32, 10).unwrap();
+        let mut bounds = Bounds::default();
+        bounds.extend(self.knots.iter().copied());
+        write!(f, "{}", bounds.draw(self, None))
+    }
+}
+
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+struct Bounds {
+    min: Point,
+    max: Point,
+}
+
+impl Bounds {
+    fn extend(&mut self, points: impl Iterator<Item = Point>) {
+        for p in points {
+            self.min.x = self.min.x.min(p.x);
+            self.min.y = self.min.y.min(p.y);
+            self.max.x = self.max.x.max(p.x);
+            self.max.y = self.max.y.max(p.y);
         }
-        for y in -10i32..10 {
-            let y = -y;
-            for x in -10i32..10 {
-                write!(f, "{}", grid[(x + 10) as usize][(y + 9) as usize]).unwrap();
+    }
+
+    fn width(&self) -> usize {
+        (self.max.x - self.min.x + 1) as usize
+    }
+
+    fn height(&self) -> usize {
+        (self.max.y - self.min.y + 1) as usize
+    }
+
+    fn draw(&self, rope: &Rope, trail: Option<&Visited>) -> String {
+        let mut out = String::with_capacity((self.width() + 1) * self.height());
+        for y in (self.min.y..=self.max.y).rev() {
+            for x in self.min.x..=self.max.x {
+                let p = Point { x, y };
+                let c = match rope.knots.iter().position(|k| *k == p) {
+                    Some(0) => 'H',
+                    Some(i) => char::from_digit(i as u32, 10).unwrap_or('+'),
+                    None if p == Point::default() => 's',
+                    None if trail.is_some_and(|t| t.contains(&p)) => '#',
+                    None => '.',
+                };
+                out.push(c);
             }
-            writeln!(f).unwrap();
+            out.push('\n');
         }
-        Ok(())
+        out
     }
 }
 
-impl NewRope {
-    fn appy_move(&mut self, d: Direction) {
-        self.pieces[0].appy_move(d);
-        for i in 0..self.pieces.len() {
-            let prev = self.pieces.get(i).unwrap();
-            let prev_tail = prev.tail;
-            if let Some(next) = self.pieces.get_mut(i + 1) {
-                next.head = prev_tail;
-                next.update_tail();
+struct Animation {
+    bounds: Bounds,
+    delay: Duration,
+    trail_knot: usize,
+}
+
+impl Animation {
+    fn new(delay: Duration, trail_knot: usize) -> Self {
+        Self {
+            bounds: Bounds::default(),
+            delay,
+            trail_knot,
+        }
+    }
+
+    fn frame(&mut self, rope: &Rope) -> String {
+        let trail = rope.visited(self.trail_knot);
+        self.bounds
+            .extend(rope.knots.iter().copied().chain(trail.iter()));
+        self.bounds.draw(rope, Some(trail))
+    }
+
+    // Calls `f` with the current frame, then with a new frame after every step
+    fn each_frame(
+        &mut self,
+        rope: &mut Rope,
+        moves: &[Move],
+        mut f: impl FnMut(&str) -> std::io::Result<()>,
+    ) -> std::io::Result<()> {
+        f(&self.frame(rope))?;
+        for m in moves {
+            for _ in 0..m.count {
+                rope.step(m.direction);
+                f(&self.frame(rope))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn frames(&mut self, rope: &mut Rope, moves: &[Move]) -> Vec<String> {
+        let mut frames = vec![];
+        self.each_frame(rope, moves, |frame| {
+            frames.push(frame.to_string());
+            Ok(())
+        })
+        .expect("collecting frames does not fail");
+        frames
+    }
+
+    // Bounds of every frame, found by replaying the moves on a copy of the knots
+    fn measure(&self, rope: &Rope, moves: &[Move]) -> Bounds {
+        let mut bounds = self.bounds;
+        bounds.extend(rope.visited(self.trail_knot).iter());
+        let mut knots = Rope {
+            knots: rope.knots.clone(),
+            rule: rope.rule,
+            visited: vec![Visited::default(); rope.knots.len()],
+        };
+        bounds.extend(knots.knots.iter().copied());
+        for m in moves {
+            for _ in 0..m.count {
+                knots.step(m.direction);
+                bounds.extend(knots.knots.iter().copied());
             }
         }
+        bounds
+    }
+
+    fn play(
+        &mut self,
+        rope: &mut Rope,
+        moves: &[Move],
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let delay = self.delay;
+        self.each_frame(rope, moves, |frame| {
+            write!(out, "\x1b[2J\x1b[H{frame}")?;
+            out.flush()?;
+            std::thread::sleep(delay);
+            Ok(())
+        })
+    }
+
+    // asciicast v2: the terminal size goes in the header, so the bounds are
+    // measured first and the frames are then written one event line at a time.
+    fn asciicast(
+        &mut self,
+        rope: &mut Rope,
+        moves: &[Move],
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        self.bounds = self.measure(rope, moves);
+        let header = serde_json::json!({
+            "version": 2,
+            "width": self.bounds.width(),
+            "height": self.bounds.height(),
+        });
+        writeln!(out, "{header}")?;
+        let delay = self.delay.as_secs_f64();
+        let mut i = 0;
+        self.each_frame(rope, moves, |frame| {
+            let data = format!("\x1b[2J\x1b[H{}", frame.replace('\n', "\r\n"));
+            writeln!(out, "{}", serde_json::json!([delay * i as f64, "o", data]))?;
+            i += 1;
+            Ok(())
+        })
     }
 }
 
 impl Rope {
-    fn appy_move(&mut self, d: Direction) {
-        self.head.move_in_direction(d);
-        self.update_tail();
+    fn new(knots: usize, rule: FollowRule) -> Self {
+        assert!(knots > 0, "a rope needs at least one knot");
+        Self {
+            knots: vec![Point::default(); knots],
+            rule,
+            visited: vec![Visited::from([Point::default()]); knots],
+        }
+    }
+
+    fn head(&self) -> Point {
+        self.knots[0]
+    }
+
+    fn tail(&self) -> Point {
+        *self.knots.last().expect("at least one knot")
+    }
+
+    fn visited(&self, knot: usize) -> &Visited {
+        &self.visited[knot]
+    }
+
+    fn step(&mut self, d: Direction) {
+        self.knots[0].move_in_direction(d);
+        self.visited[0].insert(self.knots[0]);
+        for i in 1..self.knots.len() {
+            let next = self.rule.follow(self.knots[i - 1], self.knots[i]);
+            if next == self.knots[i] {
+                break;
+            }
+            self.knots[i] = next;
+            self.visited[i].insert(next);
+        }
+    }
+
+    // Once every knot moved exactly one cell along the move's direction, the
+    // rope is strung out in a straight line and each following step is a plain
+    // translation, so the rest of the run can be applied in one go.
+    fn apply_move(&mut self, m: &Move) {
+        let mut remaining = m.count;
+        let unit = Point::default().moved(m.direction, 1);
+        while remaining > 0 {
+            let before = self.knots.clone();
+            self.step(m.direction);
+            remaining -= 1;
+            if self
+                .knots
+                .iter()
+                .zip(before.iter())
+                .all(|(after, before)| after.x - before.x == unit.x && after.y - before.y == unit.y)
+            {
+                break;
+            }
+        }
+        if remaining == 0 {
+            return;
+        }
+        for (knot, visited) in self.knots.iter_mut().zip(self.visited.iter_mut()) {
+            visited.insert_run(*knot, m.direction, remaining);
+            *knot = knot.moved(m.direction, remaining as i32);
+        }
+    }
+}
+
+fn simulate(moves: &[Move], knots: usize, rule: FollowRule) -> Rope {
+    let mut rope = Rope::new(knots, rule);
+    for m in moves {
+        rope.apply_move(m);
+    }
+    rope
+}
+
+// Merges `start..=end` into the disjoint intervals of one line, returns the
+// parts of it that were not covered yet.
+fn merge_interval(line: &mut BTreeMap<i32, i32>, mut start: i32, mut end: i32) -> Vec<(i32, i32)> {
+    let touching = line
+        .range(..=end.saturating_add(1))
+        .rev()
+        .take_while(|(_, e)| **e >= start.saturating_sub(1))
+        .map(|(s, e)| (*s, *e))
+        .collect::<Vec<_>>();
+    let mut added = vec![];
+    let mut next = start;
+    for (s, e) in touching.iter().rev() {
+        if *s > next {
+            added.push((next, s - 1));
+        }
+        next = next.max(e + 1);
+    }
+    if next <= end {
+        added.push((next, end));
+    }
+    for (s, e) in touching {
+        line.remove(&s);
+        start = start.min(s);
+        end = end.max(e);
+    }
+    line.insert(start, end);
+    added
+}
+
+fn covers(lines: &BTreeMap<i32, BTreeMap<i32, i32>>, line: i32, pos: i32) -> bool {
+    lines.get(&line).is_some_and(|intervals| {
+        intervals
+            .range(..=pos)
+            .next_back()
+            .is_some_and(|(_, end)| *end >= pos)
+    })
+}
+
+// Horizontal cells are kept as row intervals and vertical runs as column
+// intervals, a cell may be in both.
+#[derive(Debug, Default, Clone)]
+struct Visited {
+    rows: BTreeMap<i32, BTreeMap<i32, i32>>,
+    columns: BTreeMap<i32, BTreeMap<i32, i32>>,
+    len: usize,
+}
+
+impl<const N: usize> From<[Point; N]> for Visited {
+    fn from(points: [Point; N]) -> Self {
+        let mut visited = Visited::default();
+        for p in points {
+            visited.insert(p);
+        }
+        visited
     }
+}
 
-    fn update_tail(&mut self) {
-        let vec = self.vec();
+impl Visited {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn contains(&self, p: &Point) -> bool {
+        covers(&self.rows, p.y, p.x) || covers(&self.columns, p.x, p.y)
+    }
 
-        if vec.x.abs() > 1 && vec.y.abs() >= 1 || vec.x.abs() >= 1 && vec.y.abs() > 1 {
-            self.tail.x += vec.x.signum();
-            self.tail.y += vec.y.signum();
-        } else if vec.x.abs() > 1 {
-            self.tail.x += vec.x.signum();
-        } else if vec.y.abs() > 1 {
-            self.tail.y += vec.y.signum();
+    fn insert(&mut self, p: Point) {
+        self.insert_range(p.y, p.x, p.x);
+    }
+
+    fn insert_range(&mut self, y: i32, start: i32, end: i32) {
+        for (s, e) in merge_interval(self.rows.entry(y).or_default(), start, end) {
+            let in_columns = self
+                .columns
+                .range(s..=e)
+                .filter(|(x, _)| covers(&self.columns, **x, y))
+                .count();
+            self.len += (e - s + 1) as usize - in_columns;
         }
     }
 
-    fn vec(&self) -> Point {
-        Point {
-            x: self.head.x - self.tail.x,
-            y: self.head.y - self.tail.y,
+    fn insert_column_range(&mut self, x: i32, start: i32, end: i32) {
+        for (s, e) in merge_interval(self.columns.entry(x).or_default(), start, end) {
+            let in_rows = self
+                .rows
+                .range(s..=e)
+                .filter(|(y, _)| covers(&self.rows, **y, x))
+                .count();
+            self.len += (e - s + 1) as usize - in_rows;
         }
     }
 
-    fn len(&self) -> f64 {
-        let v = self.vec();
-        f64::from(v.x * v.x + v.y * v.y).sqrt()
+    // Cells strictly after `from` up to `count` steps along `d`.
+    fn insert_run(&mut self, from: Point, d: Direction, count: usize) {
+        let to = from.moved(d, count as i32);
+        match d {
+            Right => self.insert_range(from.y, from.x + 1, to.x),
+            Left => self.insert_range(from.y, to.x, from.x - 1),
+            Up => self.insert_column_range(from.x, from.y + 1, to.y),
+            Down => self.insert_column_range(from.x, to.y, from.y - 1),
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        let rows = self.rows.iter().flat_map(|(y, row)| {
+            row.iter()
+                .flat_map(move |(s, e)| (*s..=*e).map(move |x| Point { x, y: *y }))
+        });
+        let columns = self.columns.iter().flat_map(move |(x, column)| {
+            column.iter().flat_map(move |(s, e)| {
+                (*s..=*e)
+                    .filter(move |y| !covers(&self.rows, *y, *x))
+                    .map(move |y| Point { x: *x, y })
+            })
+        });
+        rows.chain(columns)
     }
 }
 
 impl Point {
+    fn moved(self, d: Direction, n: i32) -> Point {
+        match d {
+            Up => Point {
+                x: self.x,
+                y: self.y + n,
+            },
+            Down => Point {
+                x: self.x,
+                y: self.y - n,
+            },
+            Right => Point {
+                x: self.x + n,
+                y: self.y,
+            },
+            Left => Point {
+                x: self.x - n,
+                y: self.y,
+            },
+        }
+    }
+
     fn move_in_direction(&mut self, d: Direction) {
         match d {
             Up => {
@@ -206,21 +559,15 @@ R 2";
     #[test]
     fn it_moves() {
         let mut rope = Rope::default();
-        rope.appy_move(Up);
-        assert_eq!(rope.head.x, 0);
-        assert_eq!(rope.head.y, 1);
-        assert_eq!(rope.tail.x, 0);
-        assert_eq!(rope.tail.y, 0);
-        rope.appy_move(Right);
-        assert_eq!(rope.head.x, 1);
-        assert_eq!(rope.head.y, 1);
-        assert_eq!(rope.tail.x, 0);
-        assert_eq!(rope.tail.y, 0);
-        rope.appy_move(Right);
-        assert_eq!(rope.head.x, 2);
-        assert_eq!(rope.head.y, 1);
-        assert_eq!(rope.tail.x, 1);
-        assert_eq!(rope.tail.y, 1);
+        rope.step(Up);
+        assert_eq!(rope.head(), Point { x: 0, y: 1 });
+        assert_eq!(rope.tail(), Point { x: 0, y: 0 });
+        rope.step(Right);
+        assert_eq!(rope.head(), Point { x: 1, y: 1 });
+        assert_eq!(rope.tail(), Point { x: 0, y: 0 });
+        rope.step(Right);
+        assert_eq!(rope.head(), Point { x: 2, y: 1 });
+        assert_eq!(rope.tail(), Point { x: 1, y: 1 });
     }
 
     const INPUT2: &str = r"R 5
@@ -236,28 +583,217 @@ U 20";
     fn it_moves2() {
         let moves = parse(INPUT2);
 
-        let mut positions = HashSet::new();
-        let mut rope = NewRope::default();
+        let mut rope = Rope::new(10, FollowRule::Chebyshev);
         for m in moves {
             println!("{:?}", m);
-            println!("{}", rope);
-            for i in 0..m.count {
-                println!("{:?}", i);
-                rope.appy_move(m.direction);
-                positions.insert(rope.pieces.last().unwrap().head);
+            for _ in 0..m.count {
+                rope.step(m.direction);
                 println!("{}", rope);
-                println!("{:?}", rope);
-                // print_positions(&positions);
             }
         }
-        assert_eq!(positions.len(), 36);
+        assert_eq!(rope.visited(9).len(), 36);
+    }
+
+    #[test]
+    fn it_draws_rope() {
+        let mut rope = Rope::new(3, FollowRule::Chebyshev);
+        for _ in 0..3 {
+            rope.step(Right);
+        }
+        rope.step(Up);
+        assert_eq!(rope.to_string(), "...H\ns21.\n");
+    }
+
+    #[test]
+    fn it_grows_animation_bounds() {
+        let moves = parse(INPUT2);
+        let mut rope = Rope::new(10, FollowRule::Chebyshev);
+        let mut animation = Animation::new(Duration::ZERO, 9);
+        let frames = animation.frames(&mut rope, &moves);
+        assert_eq!(
+            frames.len(),
+            1 + moves.iter().map(|m| m.count).sum::<usize>()
+        );
+        assert_eq!(
+            animation.bounds,
+            Bounds {
+                min: Point { x: -11, y: -5 },
+                max: Point { x: 14, y: 15 }
+            }
+        );
+        let last = frames.last().unwrap();
+        assert_eq!(last.lines().count(), 21);
+        let trail = rope
+            .visited(9)
+            .iter()
+            .filter(|p| *p != Point::default() && !rope.knots.contains(p))
+            .count();
+        assert_eq!(last.matches('#').count(), trail);
+        println!("{last}");
+    }
+
+    #[test]
+    fn it_plays_and_records() {
+        let moves = parse(INPUT);
+        let mut out = Vec::new();
+        Animation::new(Duration::ZERO, 1)
+            .play(&mut Rope::default(), &moves, &mut out)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.matches("\x1b[2J").count(), 25);
+
+        let mut cast = Vec::new();
+        let mut animation = Animation::new(Duration::from_millis(100), 1);
+        animation
+            .asciicast(&mut Rope::default(), &moves, &mut cast)
+            .unwrap();
+        let cast = String::from_utf8(cast).unwrap();
+        let mut lines = cast.lines();
+        let header: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(header["version"], 2);
+        assert_eq!(header["width"], 6);
+        assert_eq!(header["height"], 5);
+        let events = lines
+            .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(events.len(), 25);
+        assert_eq!(events[1][0], 0.1);
+        assert_eq!(events[1][1], "o");
+        // every frame is drawn with the final size, the first one included
+        let first = events[0][2].as_str().unwrap();
+        assert_eq!(first.matches("\r\n").count(), 5);
+    }
+
+    #[test]
+    fn it_keeps_visited_cells_as_ranges() {
+        let mut visited = Visited::default();
+        visited.insert(Point { x: 0, y: 0 });
+        visited.insert(Point { x: 2, y: 0 });
+        assert_eq!(visited.len(), 2);
+        visited.insert_run(Point { x: -3, y: 0 }, Right, 7);
+        assert_eq!(visited.len(), 7);
+        assert_eq!(visited.rows[&0].len(), 1);
+        visited.insert_run(Point { x: 1, y: 0 }, Down, 2);
+        visited.insert_run(Point { x: 1, y: -2 }, Left, 2);
+        assert_eq!(visited.len(), 11);
+        assert!(visited.contains(&Point { x: -1, y: -2 }));
+        assert!(!visited.contains(&Point { x: 2, y: -2 }));
+        assert_eq!(visited.iter().count(), visited.len());
+    }
+
+    #[test]
+    fn it_applies_runs_like_unit_steps() {
+        for rule in [
+            FollowRule::Chebyshev,
+            FollowRule::FourConnected,
+            FollowRule::Slack(3),
+        ] {
+            for input in [
+                INPUT,
+                INPUT2,
+                "R 5000\nU 100\nL 5000\nD 3",
+                "U 5000\nR 2\nD 5000\nL 3\nU 7\nR 9\nD 4",
+            ] {
+                let moves = parse(input);
+                let mut stepped = Rope::new(10, rule);
+                for m in &moves {
+                    for _ in 0..m.count {
+                        stepped.step(m.direction);
+                    }
+                }
+                let bulk = simulate(&moves, 10, rule);
+                assert_eq!(bulk.knots, stepped.knots);
+                for knot in 0..10 {
+                    assert_eq!(bulk.visited(knot).len(), stepped.visited(knot).len());
+                    assert_eq!(bulk.visited(knot).iter().count(), bulk.visited(knot).len());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_applies_huge_runs() {
+        let moves = parse("R 5000000\nU 1000\nL 5000000\nD 3");
+        let rope = simulate(&moves, 10, FollowRule::Chebyshev);
+        assert_eq!(rope.head(), Point { x: 0, y: 997 });
+        assert_eq!(rope.tail(), Point { x: 8, y: 999 });
+        assert_eq!(rope.visited(0).len(), 1 + 5_000_000 + 1000 + 5_000_000 + 3);
+        assert!(rope.visited(9).contains(&Point { x: 4_999_991, y: 0 }));
+        assert!(!rope.visited(9).contains(&Point { x: 4_999_992, y: 0 }));
+    }
+
+    #[test]
+    fn it_applies_huge_vertical_runs() {
+        let moves = parse("U 3000000\nR 2\nD 3000000\nL 1\nU 5");
+        let rope = simulate(&moves, 10, FollowRule::Chebyshev);
+        assert_eq!(rope.head(), Point { x: 1, y: 5 });
+        assert_eq!(rope.visited(0).len(), 1 + 3_000_000 + 2 + 3_000_000 + 1 + 5);
+        assert!(rope.visited(9).contains(&Point { x: 0, y: 2_999_991 }));
+        assert!(!rope.visited(9).contains(&Point { x: 0, y: 2_999_992 }));
+        assert!(rope.visited(9).columns.len() <= 3);
+        assert!(rope.visited(9).rows.len() < 100);
+    }
+
+    fn assert_rule_holds(input: &str, knots: usize, rule: FollowRule) -> Rope {
+        let mut rope = Rope::new(knots, rule);
+        for m in parse(input) {
+            for _ in 0..m.count {
+                rope.step(m.direction);
+                for pair in rope.knots.windows(2) {
+                    let dx = (pair[0].x - pair[1].x).abs();
+                    let dy = (pair[0].y - pair[1].y).abs();
+                    match rule {
+                        FollowRule::Chebyshev => assert!(dx <= 1 && dy <= 1),
+                        FollowRule::FourConnected => assert!(dx + dy <= 1),
+                        FollowRule::Slack(slack) => assert!(dx <= slack && dy <= slack),
+                    }
+                }
+            }
+        }
+        rope
+    }
+
+    #[test]
+    fn it_follows_chebyshev() {
+        let rope = assert_rule_holds(INPUT, 2, FollowRule::Chebyshev);
+        assert_eq!(rope.visited(1).len(), 13);
+        let rope = assert_rule_holds(INPUT, 10, FollowRule::Chebyshev);
+        assert_eq!(rope.visited(1).len(), 13);
+        assert_eq!(rope.visited(9).len(), 1);
+        let rope = assert_rule_holds(INPUT2, 10, FollowRule::Chebyshev);
+        assert_eq!(rope.visited(9).len(), 36);
+    }
+
+    #[test]
+    fn it_follows_four_connected() {
+        let rope = assert_rule_holds("R 2\nU 2", 2, FollowRule::FourConnected);
+        assert_eq!(rope.tail(), Point { x: 1, y: 2 });
+        assert_eq!(rope.visited(1).len(), 4);
+        let rope = assert_rule_holds("R 3\nU 1", 3, FollowRule::FourConnected);
+        assert_eq!(rope.knots[1], Point { x: 2, y: 1 });
+        assert_eq!(rope.tail(), Point { x: 1, y: 1 });
+        assert_rule_holds(INPUT, 10, FollowRule::FourConnected);
+        assert_rule_holds(INPUT2, 10, FollowRule::FourConnected);
+    }
+
+    #[test]
+    fn it_follows_with_slack() {
+        let rope = assert_rule_holds("R 4", 2, FollowRule::Slack(2));
+        assert_eq!(rope.tail(), Point { x: 2, y: 0 });
+        assert_eq!(rope.visited(1).len(), 3);
+        let rope = assert_rule_holds("R 4\nU 3", 2, FollowRule::Slack(2));
+        assert_eq!(rope.tail(), Point { x: 3, y: 1 });
+        assert_rule_holds(INPUT, 10, FollowRule::Slack(3));
+        assert_rule_holds(INPUT2, 10, FollowRule::Slack(3));
     }
 }
 
 fn print_positions(pos: &HashSet<Point>) {
+    let mut bounds = Bounds::default();
+    bounds.extend(pos.iter().copied());
     println!();
-    for x in -10..10 {
-        for y in -10..10 {
+    for y in (bounds.min.y..=bounds.max.y).rev() {
+        for x in bounds.min.x..=bounds.max.x {
             if pos.contains(&Point { x, y }) {
                 print!("#");
             } else {
@@ -272,29 +808,13 @@ fn print_positions(pos: &HashSet<Point>) {
 #[test]
 fn part1() {
     let moves = parse(INPUT);
-
-    let mut positions = HashSet::new();
-    let mut rope = Rope::default();
-    for m in moves {
-        for _ in 0..m.count {
-            rope.appy_move(m.direction);
-            positions.insert(rope.tail);
-        }
-    }
-    println!("{:?}", positions.len());
+    let rope = simulate(&moves, 2, FollowRule::Chebyshev);
+    println!("{:?}", rope.visited(1).len());
 }
 
 #[test]
 fn part2() {
     let moves = parse(INPUT);
-
-    let mut positions = HashSet::new();
-    let mut rope = NewRope::default();
-    for m in moves {
-        for _ in 0..m.count {
-            rope.appy_move(m.direction);
-            positions.insert(rope.pieces.last().unwrap().head);
-        }
-    }
-    println!("{:?}", positions.len());
+    let rope = simulate(&moves, 10, FollowRule::Chebyshev);
+    println!("{:?}", rope.visited(9).len());
 }
@@ -1,11 +1,22 @@
 use serde_json::Value;
 use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 const INPUT: &str = include_str!("input.txt");
 
-#[derive(Eq, PartialEq, Debug, Clone)]
+#[derive(Debug, Clone)]
 struct MyData(serde_json::value::Value);
 
+// Equal exactly when the comparator says so, e.g. `2.0 == 2` and `[1] == 1`
+impl PartialEq for MyData {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for MyData {}
+
 impl PartialOrd<Self> for MyData {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -14,41 +25,405 @@ impl PartialOrd<Self> for MyData {
 
 impl Ord for MyData {
     fn cmp(&self, other: &Self) -> Ordering {
-        match (&self.0, &other.0) {
-            (Value::Array(a), Value::Array(b)) => {
-                for i in 0..a.len() {
-                    let a = a.get(i).unwrap().clone();
-                    let Some(b) = b.get(i) else {
-                        return Ordering::Greater;
-                    };
-                    match MyData(a).cmp(&MyData(b.clone())) {
-                        Ordering::Equal => {
-                            continue;
-                        }
-                        other => {
-                            return other;
-                        }
+        Comparator::default().cmp(&self.0, &other.0)
+    }
+}
+
+// How values of different kinds compare
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+enum Coercion {
+    // puzzle rule: a scalar against an array is wrapped in an array first
+    #[default]
+    WrapScalar,
+    KindRank,
+}
+
+// Order over any JSON value. Objects compare as their sorted (key, value)
+// pairs, and kinds as null < bool < number < string < array < object.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+struct Comparator {
+    coercion: Coercion,
+}
+
+impl Comparator {
+    fn kind_rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+            Value::Array(_) => 4,
+            Value::Object(_) => 5,
+        }
+    }
+
+    fn cmp_numbers(a: &serde_json::Number, b: &serde_json::Number) -> Ordering {
+        let as_int = |n: &serde_json::Number| {
+            n.as_i64()
+                .map(i128::from)
+                .or_else(|| n.as_u64().map(i128::from))
+        };
+        let as_float = |n: &serde_json::Number| n.as_f64().expect("a finite number");
+        match (as_int(a), as_int(b)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(a), None) => Comparator::cmp_int_float(a, as_float(b)),
+            (None, Some(b)) => Comparator::cmp_int_float(b, as_float(a)).reverse(),
+            (None, None) => as_float(a)
+                .partial_cmp(&as_float(b))
+                .expect("finite numbers"),
+        }
+    }
+
+    // Exact, an integer past 2^53 is not rounded to the nearest float
+    fn cmp_int_float(int: i128, float: f64) -> Ordering {
+        let whole = float.trunc();
+        if whole >= i128::MAX as f64 {
+            return Ordering::Less;
+        }
+        if whole < i128::MIN as f64 {
+            return Ordering::Greater;
+        }
+        int.cmp(&(whole as i128))
+            .then_with(|| 0.0.partial_cmp(&(float - whole)).expect("a finite number"))
+    }
+
+    fn cmp_slices(&self, a: &[Value], b: &[Value]) -> Ordering {
+        for (a, b) in a.iter().zip(b.iter()) {
+            match self.cmp(a, b) {
+                Ordering::Equal => continue,
+                other => return other,
+            }
+        }
+        a.len().cmp(&b.len())
+    }
+
+    fn cmp(&self, a: &Value, b: &Value) -> Ordering {
+        match (a, b) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Number(a), Value::Number(b)) => Comparator::cmp_numbers(a, b),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Array(a), Value::Array(b)) => self.cmp_slices(a, b),
+            (Value::Object(a), Value::Object(b)) => {
+                for ((ka, va), (kb, vb)) in a.iter().zip(b.iter()) {
+                    match ka.cmp(kb).then_with(|| self.cmp(va, vb)) {
+                        Ordering::Equal => continue,
+                        other => return other,
+                    }
+                }
+                a.len().cmp(&b.len())
+            }
+            (Value::Array(a), b) if self.coercion == Coercion::WrapScalar && !b.is_object() => {
+                self.cmp_slices(a, std::slice::from_ref(b))
+            }
+            (a, Value::Array(b)) if self.coercion == Coercion::WrapScalar && !a.is_object() => {
+                self.cmp_slices(std::slice::from_ref(a), b)
+            }
+            (a, b) => Comparator::kind_rank(a).cmp(&Comparator::kind_rank(b)),
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+enum Packet {
+    Int(u64),
+    List(Vec<Packet>),
+}
+
+impl PartialOrd<Self> for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Packet::Int(a), Packet::Int(b)) => a.cmp(b),
+            (Packet::List(a), Packet::List(b)) => a.iter().cmp(b.iter()),
+            (Packet::Int(_), Packet::List(b)) => std::slice::from_ref(self).iter().cmp(b.iter()),
+            (Packet::List(a), Packet::Int(_)) => a.iter().cmp(std::slice::from_ref(other).iter()),
+        }
+    }
+}
+
+impl Display for Packet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Packet::Int(i) => write!(f, "{i}"),
+            Packet::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
                     }
+                    write!(f, "{item}")?;
                 }
-                if a.len() == b.len() {
-                    return Ordering::Equal;
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct Step {
+    depth: usize,
+    message: String,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct Explanation {
+    ordering: Ordering,
+    steps: Vec<Step>,
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}- {}", "  ".repeat(step.depth), step.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl Packet {
+    fn explain_cmp(&self, other: &Self) -> Explanation {
+        let mut steps = vec![];
+        let ordering = Packet::explain(self, other, 0, &mut steps);
+        Explanation { ordering, steps }
+    }
+
+    fn explain(left: &Packet, right: &Packet, depth: usize, steps: &mut Vec<Step>) -> Ordering {
+        let step = |steps: &mut Vec<Step>, message: String| {
+            steps.push(Step {
+                depth: depth + 1,
+                message,
+            })
+        };
+        steps.push(Step {
+            depth,
+            message: format!("Compare {left} vs {right}"),
+        });
+        let (ordering, reason) = match (left, right) {
+            (Packet::Int(a), Packet::Int(b)) => (a.cmp(b), "is smaller"),
+            (Packet::Int(_), Packet::List(_)) => {
+                let left = Packet::List(vec![left.clone()]);
+                step(
+                    steps,
+                    format!("Mixed types; convert left to {left} and retry comparison"),
+                );
+                return Packet::explain(&left, right, depth + 1, steps);
+            }
+            (Packet::List(_), Packet::Int(_)) => {
+                let right = Packet::List(vec![right.clone()]);
+                step(
+                    steps,
+                    format!("Mixed types; convert right to {right} and retry comparison"),
+                );
+                return Packet::explain(left, &right, depth + 1, steps);
+            }
+            (Packet::List(a), Packet::List(b)) => {
+                for (a, b) in a.iter().zip(b.iter()) {
+                    match Packet::explain(a, b, depth + 1, steps) {
+                        Ordering::Equal => continue,
+                        other => return other,
+                    }
                 }
-                Ordering::Less
+                (a.len().cmp(&b.len()), "ran out of items")
             }
-            (Value::Number(a), Value::Number(b)) => a.as_u64().unwrap().cmp(&b.as_u64().unwrap()),
-            (Value::Array(_), Value::Number(_)) => {
-                self.cmp(&MyData(Value::Array(vec![other.0.clone()])))
+        };
+        let message = match ordering {
+            Ordering::Equal => return Ordering::Equal,
+            Ordering::Less => format!("Left side {reason}, so inputs are in the right order"),
+            Ordering::Greater => {
+                format!("Right side {reason}, so inputs are not in the right order")
             }
-            (Value::Number(_), Value::Array(_)) => {
-                MyData(Value::Array(vec![self.0.clone()])).cmp(other)
+        };
+        step(steps, message);
+        ordering
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum PacketParseError {
+    UnexpectedEnd(usize),
+    UnexpectedChar(usize, char),
+    Overflow(usize),
+    TrailingInput(usize),
+}
+
+impl Display for PacketParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PacketParseError::UnexpectedEnd(pos) => write!(f, "unexpected end at position {pos}"),
+            PacketParseError::UnexpectedChar(pos, c) => {
+                write!(f, "unexpected {c:?} at position {pos}")
             }
-            (_, _) => {
-                panic!("don't care for this one")
+            PacketParseError::Overflow(pos) => write!(f, "integer too large at position {pos}"),
+            PacketParseError::TrailingInput(pos) => {
+                write!(f, "trailing input at position {pos}")
             }
         }
     }
 }
 
+// A divider packet given on the command line that does not parse
+#[derive(Debug, Eq, PartialEq)]
+pub struct DividerError {
+    argument: String,
+    error: PacketParseError,
+}
+
+impl Display for DividerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid divider packet {:?}: {}",
+            self.argument, self.error
+        )
+    }
+}
+
+struct PacketParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PacketParser<'a> {
+    fn peek(&self) -> Result<u8, PacketParseError> {
+        self.input
+            .get(self.pos)
+            .copied()
+            .ok_or(PacketParseError::UnexpectedEnd(self.pos))
+    }
+
+    fn unexpected(&self, c: u8) -> PacketParseError {
+        PacketParseError::UnexpectedChar(self.pos, c as char)
+    }
+
+    fn packet(&mut self) -> Result<Packet, PacketParseError> {
+        match self.peek()? {
+            b'[' => self.list(),
+            b'0'..=b'9' => self.int(),
+            c => Err(self.unexpected(c)),
+        }
+    }
+
+    fn int(&mut self) -> Result<Packet, PacketParseError> {
+        let start = self.pos;
+        // no leading zeros, so each number has a single spelling
+        if let [b'0', b'0'..=b'9', ..] = &self.input[start..] {
+            return Err(PacketParseError::UnexpectedChar(start, '0'));
+        }
+        let mut value: u64 = 0;
+        while let Some(c @ b'0'..=b'9') = self.input.get(self.pos).copied() {
+            value = value
+                .checked_mul(10)
+                .and_then(|v| v.checked_add(u64::from(c - b'0')))
+                .ok_or(PacketParseError::Overflow(start))?;
+            self.pos += 1;
+        }
+        Ok(Packet::Int(value))
+    }
+
+    fn list(&mut self) -> Result<Packet, PacketParseError> {
+        self.pos += 1;
+        let mut items = vec![];
+        if self.peek()? == b']' {
+            self.pos += 1;
+            return Ok(Packet::List(items));
+        }
+        loop {
+            items.push(self.packet()?);
+            match self.peek()? {
+                b',' => self.pos += 1,
+                b']' => {
+                    self.pos += 1;
+                    return Ok(Packet::List(items));
+                }
+                c => return Err(self.unexpected(c)),
+            }
+        }
+    }
+}
+
+impl FromStr for Packet {
+    type Err = PacketParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = PacketParser {
+            input: s.as_bytes(),
+            pos: 0,
+        };
+        let packet = parser.packet()?;
+        if parser.pos != s.len() {
+            return Err(PacketParseError::TrailingInput(parser.pos));
+        }
+        Ok(packet)
+    }
+}
+
+fn parse_packets(input: &str) -> Vec<Vec<Packet>> {
+    input
+        .split("\n\n")
+        .map(|x| {
+            x.lines()
+                .map(|l| {
+                    l.parse()
+                        .unwrap_or_else(|e| panic!("invalid packet {l:?}: {e}"))
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>()
+}
+
+fn divider_positions(packets: &[Packet], dividers: &[Packet]) -> Vec<usize> {
+    dividers
+        .iter()
+        .enumerate()
+        .map(|(i, divider)| {
+            let before_packets = packets.iter().filter(|p| *p < divider).count();
+            let before_dividers = dividers
+                .iter()
+                .enumerate()
+                .filter(|(j, other)| *other < divider || (*other == divider && *j < i))
+                .count();
+            1 + before_packets + before_dividers
+        })
+        .collect()
+}
+
+fn dividers_from_args(args: impl IntoIterator<Item = String>) -> Result<Vec<Packet>, DividerError> {
+    let dividers = args
+        .into_iter()
+        .map(|argument| {
+            argument
+                .parse()
+                .map_err(|error| DividerError { argument, error })
+        })
+        .collect::<Result<Vec<Packet>, _>>()?;
+    if dividers.is_empty() {
+        return Ok(["[[2]]", "[[6]]"]
+            .iter()
+            .map(|d| d.parse().expect("a valid packet"))
+            .collect());
+    }
+    Ok(dividers)
+}
+
+pub fn decoder_key_from_args(
+    args: impl IntoIterator<Item = String>,
+) -> Result<(Vec<usize>, usize), DividerError> {
+    let dividers = dividers_from_args(args)?;
+    let packets = parse_packets(INPUT)
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    let positions = divider_positions(&packets, &dividers);
+    let key = positions.iter().product();
+    Ok((positions, key))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -95,6 +470,213 @@ mod test {
         assert_eq!(data[6][0].cmp(&data[6][1]), Ordering::Greater);
         assert_eq!(data[7][0].cmp(&data[7][1]), Ordering::Greater);
     }
+
+    #[test]
+    fn it_cmp_extended_values() {
+        let cmp = |a: &str, b: &str, coercion| {
+            Comparator { coercion }.cmp(
+                &serde_json::from_str(a).unwrap(),
+                &serde_json::from_str(b).unwrap(),
+            )
+        };
+        let wrap = Coercion::WrapScalar;
+        assert_eq!(cmp("[-3,1]", "[-2]", wrap), Ordering::Less);
+        assert_eq!(cmp("[1.5]", "[1]", wrap), Ordering::Greater);
+        assert_eq!(cmp("2.0", "2", wrap), Ordering::Equal);
+        assert_eq!(cmp(r#"["b"]"#, r#"["ab","c"]"#, wrap), Ordering::Greater);
+        assert_eq!(cmp(r#""a""#, r#"["a",1]"#, wrap), Ordering::Less);
+        assert_eq!(cmp("[null,true]", "[false]", wrap), Ordering::Less);
+        assert_eq!(cmp("[true]", "[false,1]", wrap), Ordering::Greater);
+        assert_eq!(cmp(r#"{"a":1}"#, r#"{"a":1,"b":0}"#, wrap), Ordering::Less);
+        assert_eq!(cmp(r#"{"a":[1]}"#, r#"{"a":1}"#, wrap), Ordering::Equal);
+        assert_eq!(cmp("18446744073709551615", "-1", wrap), Ordering::Greater);
+        assert_eq!(cmp("-0.0", "0", wrap), Ordering::Equal);
+        assert_eq!(cmp("-0.0", "0.0", wrap), Ordering::Equal);
+        assert_eq!(cmp("-2.5", "-2", wrap), Ordering::Less);
+        assert_eq!(cmp("-2", "-2.5", wrap), Ordering::Greater);
+        assert_eq!(
+            cmp("1e300", "18446744073709551615", wrap),
+            Ordering::Greater
+        );
+        assert_eq!(cmp("-1e300", "-9223372036854775808", wrap), Ordering::Less);
+        let (i, f, j) = ("9007199254740992", "9007199254740992.0", "9007199254740993");
+        assert_eq!(cmp(i, f, wrap), Ordering::Equal);
+        assert_eq!(cmp(f, j, wrap), Ordering::Less);
+        assert_eq!(cmp(i, j, wrap), Ordering::Less);
+
+        let rank = Coercion::KindRank;
+        assert_eq!(cmp("[9]", "[[1]]", rank), Ordering::Less);
+        assert_eq!(cmp("[[1],4]", "[[1],[4]]", rank), Ordering::Less);
+        assert_eq!(cmp(r#"[1,"1"]"#, "[1,[1]]", rank), Ordering::Less);
+        assert_eq!(cmp("[{}]", "[[]]", rank), Ordering::Greater);
+    }
+
+    #[test]
+    fn it_keeps_eq_consistent_with_ord() {
+        let data = |text: &str| MyData(serde_json::from_str(text).unwrap());
+        assert_eq!(data("2.0"), data("2"));
+        assert_eq!(data(r#"{"a":[1]}"#), data(r#"{"a":1}"#));
+        assert_ne!(data("[1,2]"), data("[1]"));
+        let values = ["2", "2.0", "[2]", "[[2.0]]", "[2,0]", "1.5", "null"].map(data);
+        for a in &values {
+            for b in &values {
+                assert_eq!(a == b, a.cmp(b) == Ordering::Equal);
+            }
+        }
+    }
+
+    #[test]
+    fn it_parses_packets() {
+        let data = parse_packets(INPUT_TEST);
+        assert_eq!(
+            data[1][1],
+            Packet::List(vec![Packet::List(vec![Packet::Int(1)]), Packet::Int(4)])
+        );
+        for line in INPUT_TEST.lines().filter(|l| !l.is_empty()) {
+            assert_eq!(line.parse::<Packet>().unwrap().to_string(), line);
+        }
+        assert_eq!("[10,[]]".parse::<Packet>().unwrap().to_string(), "[10,[]]");
+    }
+
+    #[test]
+    fn it_reports_parse_errors() {
+        assert_eq!(
+            "[1,2".parse::<Packet>(),
+            Err(PacketParseError::UnexpectedEnd(4))
+        );
+        assert_eq!(
+            "[1,,2]".parse::<Packet>(),
+            Err(PacketParseError::UnexpectedChar(3, ','))
+        );
+        assert_eq!(
+            "[1 2]".parse::<Packet>(),
+            Err(PacketParseError::UnexpectedChar(2, ' '))
+        );
+        assert_eq!(
+            "[1]]".parse::<Packet>(),
+            Err(PacketParseError::TrailingInput(3))
+        );
+        assert_eq!(
+            "[99999999999999999999]".parse::<Packet>(),
+            Err(PacketParseError::Overflow(1))
+        );
+        assert_eq!(
+            "".parse::<Packet>(),
+            Err(PacketParseError::UnexpectedEnd(0))
+        );
+        assert_eq!(
+            "[01,002]".parse::<Packet>(),
+            Err(PacketParseError::UnexpectedChar(1, '0'))
+        );
+        assert_eq!(
+            "[1,002]".parse::<Packet>(),
+            Err(PacketParseError::UnexpectedChar(3, '0'))
+        );
+        assert_eq!("[0,10]".parse::<Packet>().unwrap().to_string(), "[0,10]");
+    }
+
+    #[test]
+    fn it_cmp_packets() {
+        let data = parse_packets(INPUT_TEST);
+        let expected = [
+            Ordering::Less,
+            Ordering::Less,
+            Ordering::Greater,
+            Ordering::Less,
+            Ordering::Greater,
+            Ordering::Less,
+            Ordering::Greater,
+            Ordering::Greater,
+        ];
+        for (pair, expected) in data.iter().zip(expected) {
+            assert_eq!(pair[0].cmp(&pair[1]), expected);
+        }
+    }
+
+    #[test]
+    fn it_explains_cmp() {
+        let data = parse_packets(INPUT_TEST);
+        let explanation = data[1][0].explain_cmp(&data[1][1]);
+        assert_eq!(explanation.ordering, Ordering::Less);
+        assert_eq!(
+            explanation.to_string(),
+            r"- Compare [[1],[2,3,4]] vs [[1],4]
+  - Compare [1] vs [1]
+    - Compare 1 vs 1
+  - Compare [2,3,4] vs 4
+    - Mixed types; convert right to [4] and retry comparison
+    - Compare [2,3,4] vs [4]
+      - Compare 2 vs 4
+        - Left side is smaller, so inputs are in the right order
+"
+        );
+        assert_eq!(
+            data[3][0].explain_cmp(&data[3][1]).steps.last().unwrap(),
+            &Step {
+                depth: 1,
+                message: "Left side ran out of items, so inputs are in the right order".to_string()
+            }
+        );
+        for pair in &data {
+            assert_eq!(
+                pair[0].explain_cmp(&pair[1]).ordering,
+                pair[0].cmp(&pair[1])
+            );
+        }
+    }
+
+    #[test]
+    fn it_finds_divider_positions() {
+        let packets = parse_packets(INPUT_TEST)
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        let dividers = dividers_from_args(vec![]).unwrap();
+        assert_eq!(divider_positions(&packets, &dividers), vec![10, 14]);
+
+        let dividers =
+            dividers_from_args(["[[6]]", "[[2]]", "[[2]]", "[]"].map(String::from)).unwrap();
+        assert_eq!(divider_positions(&packets, &dividers), vec![16, 11, 12, 1]);
+
+        let err = dividers_from_args(["[[2]".to_string()]).unwrap_err();
+        assert_eq!(err.error, PacketParseError::UnexpectedEnd(4));
+        assert_eq!(
+            err.to_string(),
+            "invalid divider packet \"[[2]\": unexpected end at position 4"
+        );
+        assert_eq!(
+            dividers_from_args(["[1;2]".to_string()])
+                .unwrap_err()
+                .to_string(),
+            "invalid divider packet \"[1;2]\": unexpected ';' at position 2"
+        );
+    }
+
+    fn random_packet(seed: &mut u64, depth: usize) -> Packet {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 7;
+        *seed ^= *seed << 17;
+        if depth == 0 || seed.is_multiple_of(3) {
+            Packet::Int(*seed % 11)
+        } else {
+            let len = (*seed >> 8) % 4;
+            Packet::List((0..len).map(|_| random_packet(seed, depth - 1)).collect())
+        }
+    }
+
+    #[test]
+    fn it_orders_packets_like_my_data() {
+        let mut seed = 0x2022_1213;
+        for _ in 0..2000 {
+            let a = Packet::List(vec![random_packet(&mut seed, 4)]);
+            let b = Packet::List(vec![random_packet(&mut seed, 4)]);
+            let (a_text, b_text) = (a.to_string(), b.to_string());
+            assert_eq!(a_text.parse::<Packet>().unwrap(), a);
+            let my_a = MyData(serde_json::from_str(&a_text).unwrap());
+            let my_b = MyData(serde_json::from_str(&b_text).unwrap());
+            assert_eq!(a.cmp(&b), my_a.cmp(&my_b), "{a_text} vs {b_text}");
+        }
+    }
 }
 
 fn parse(input: &str) -> Vec<Vec<MyData>> {
@@ -110,7 +692,7 @@ fn parse(input: &str) -> Vec<Vec<MyData>> {
 
 #[test]
 fn part1() {
-    let data = parse(INPUT);
+    let data = parse_packets(INPUT);
     let res = data
         .into_iter()
         .enumerate()
@@ -122,22 +704,6 @@ fn part1() {
 
 #[test]
 fn part2() {
-    let mut data = parse(INPUT).into_iter().flatten().collect::<Vec<_>>();
-    let more_packets = r"[[2]]
-[[6]]";
-    let more_packets = parse(more_packets)
-        .into_iter()
-        .flatten()
-        .collect::<Vec<_>>();
-    data.extend(more_packets.clone());
-    data.sort_unstable();
-    let res = data
-        .into_iter()
-        .enumerate()
-        .map(|(i, p)| (i + 1, p))
-        .filter(|(_, p)| more_packets.contains(p))
-        .map(|(i, _)| i)
-        .reduce(|a, b| a * b)
-        .unwrap();
+    let (_, res) = decoder_key_from_args(vec![]).unwrap();
     println!("{:?}", res);
 }
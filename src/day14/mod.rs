@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 
 const INPUT: &str = include_str!("input.txt");
@@ -12,41 +12,102 @@ struct Point {
 }
 
 impl Point {
-    fn down(&self) -> Self {
+    fn offset(&self, by: Point) -> Self {
         Self {
-            x: self.x,
-            y: self.y + 1,
+            x: self.x + by.x,
+            y: self.y + by.y,
         }
     }
+}
 
-    fn left(&self) -> Self {
-        Self {
-            x: self.x - 1,
-            y: self.y + 1,
-        }
-    }
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+enum FloorPolicy {
+    #[default]
+    Abyss,
+    // an infinite floor this many rows below the lowest rock, at least one,
+    // built through `FloorPolicy::floor`
+    Floor(i32),
+    // walls just outside `min_x..=max_x`, with the abyss below the lowest rock
+    Walls {
+        min_x: i32,
+        max_x: i32,
+    },
+}
+
+// Built through `SandRules::new`, which checks that grains always end up
+// settling or falling out.
+#[derive(Debug, Eq, PartialEq, Clone)]
+struct SandRules {
+    // tried in order, each one goes down at least one row
+    moves: Vec<Point>,
+    // grains are emitted from each source in turn
+    sources: Vec<Point>,
+    // with `FloorPolicy::Walls`, a grain crossing a wall falls off the edge
+    slide_off_edges: bool,
+}
 
-    fn right(&self) -> Self {
+impl Default for SandRules {
+    fn default() -> Self {
         Self {
-            x: self.x + 1,
-            y: self.y + 1,
+            moves: vec![
+                Point { x: 0, y: 1 },
+                Point { x: -1, y: 1 },
+                Point { x: 1, y: 1 },
+            ],
+            sources: vec![SOURCE],
+            slide_off_edges: false,
         }
     }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum FloorError {
+    // the floor has to lie below the lowest rock
+    NotBelowRock(i32),
+}
 
-    fn will_settle(&self, s: &Structure) -> bool {
-        self.y <= s.max_y
+impl FloorPolicy {
+    fn floor(k: i32) -> Result<Self, FloorError> {
+        if k < 1 {
+            return Err(FloorError::NotBelowRock(k));
+        }
+        Ok(FloorPolicy::Floor(k))
     }
 }
 
 #[derive(Debug, Eq, PartialEq)]
-enum Part {
-    Part1,
-    Part2,
+enum RulesError {
+    NoMoves,
+    NoSources,
+    // a move that does not go down, grains could move forever
+    FlatMove(Point),
+    // a source above row 0
+    SourceAbove(Point),
 }
 
-impl Default for Part {
-    fn default() -> Self {
-        Part::Part1
+impl SandRules {
+    fn new(
+        moves: Vec<Point>,
+        sources: Vec<Point>,
+        slide_off_edges: bool,
+    ) -> Result<Self, RulesError> {
+        if moves.is_empty() {
+            return Err(RulesError::NoMoves);
+        }
+        if sources.is_empty() {
+            return Err(RulesError::NoSources);
+        }
+        if let Some(m) = moves.iter().find(|m| m.y < 1) {
+            return Err(RulesError::FlatMove(*m));
+        }
+        if let Some(source) = sources.iter().find(|s| s.y < 0) {
+            return Err(RulesError::SourceAbove(*source));
+        }
+        Ok(Self {
+            moves,
+            sources,
+            slide_off_edges,
+        })
     }
 }
 
@@ -56,7 +117,13 @@ struct Structure {
     sand: HashSet<Point>,
     falling_grain: Option<Point>,
     max_y: i32,
-    part: Part,
+    floor: FloorPolicy,
+    rules: SandRules,
+    next_source: usize,
+    turns_without_settling: usize,
+    lost: usize,
+    path: Vec<Point>,
+    last_path: Vec<Point>,
 }
 
 impl Display for Structure {
@@ -77,8 +144,8 @@ impl Display for Structure {
             min_y = min_y.min(grain.y);
             max_y = max_y.max(grain.y);
         }
-        if self.part == Part::Part2 {
-            max_y += 2;
+        if let FloorPolicy::Floor(k) = self.floor {
+            max_y += k;
         }
 
         for y in (min_y - 1)..=(max_y + 1) {
@@ -99,84 +166,207 @@ impl Display for Structure {
     }
 }
 
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum PathMode {
+    Polyline,
+    // closed paths (ending where they started) are filled in
+    Polygon,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum PathError {
+    InvalidPoint { line: usize, text: String },
+    BadSegment { line: usize, from: Point, to: Point },
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Validation {
+    // (line, point) of zero-length segments and single-point paths
+    degenerate: Vec<(usize, Point)>,
+    // (line, earlier line) -> number of rock cells drawn by both, a line
+    // paired with itself counts the cells it retraces
+    overlapping: BTreeMap<(usize, usize), usize>,
+    // lines that could not be filled as polygons and were drawn as polylines
+    unclosed: Vec<usize>,
+}
+
+impl Validation {
+    fn is_clean(&self) -> bool {
+        self.degenerate.is_empty() && self.overlapping.is_empty() && self.unclosed.is_empty()
+    }
+}
+
+fn segment_cells(from: Point, to: Point) -> Option<Vec<Point>> {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    if dx != 0 && dy != 0 && dx.abs() != dy.abs() {
+        return None;
+    }
+    let step = Point {
+        x: dx.signum(),
+        y: dy.signum(),
+    };
+    let mut cells = vec![from];
+    let mut p = from;
+    while p != to {
+        p = p.offset(step);
+        cells.push(p);
+    }
+    Some(cells)
+}
+
+// Even-odd rule, cells on the outline itself are drawn separately.
+fn inside_polygon(p: Point, polygon: &[Point]) -> bool {
+    let mut inside = false;
+    for edge in polygon.windows(2) {
+        let (a, b) = (edge[0], edge[1]);
+        if (a.y > p.y) != (b.y > p.y) {
+            let cross_x = a.x as f64 + (p.y - a.y) as f64 * (b.x - a.x) as f64 / (b.y - a.y) as f64;
+            if (p.x as f64) < cross_x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
 impl Structure {
     fn from(lines: Vec<Vec<Point>>) -> Self {
+        Structure::from_paths(&lines, PathMode::Polyline)
+            .unwrap_or_else(|e| panic!("invalid rock path: {e:?}"))
+            .0
+    }
+
+    fn from_paths(lines: &[Vec<Point>], mode: PathMode) -> Result<(Self, Validation), PathError> {
         let mut s = Structure::default();
-        for line in lines {
-            for segment in line.windows(2) {
-                if segment[0].x == segment[1].x {
-                    // vertical
-                    let x = segment[0].x;
-                    if segment[0].y <= segment[1].y {
-                        for y in segment[0].y..=segment[1].y {
-                            s.rock.insert(Point { x, y });
-                        }
-                    } else {
-                        for y in segment[1].y..=segment[0].y {
-                            s.rock.insert(Point { x, y });
-                        }
+        let mut validation = Validation::default();
+        let mut owners: HashMap<Point, usize> = HashMap::new();
+        for (i, path) in lines.iter().enumerate() {
+            let line = i + 1;
+            let mut drawn = HashSet::new();
+            if path.len() == 1 {
+                validation.degenerate.push((line, path[0]));
+                drawn.insert(path[0]);
+            }
+            let closed = path.len() > 1 && path.first() == path.last();
+            let mut retraced = 0;
+            for (k, segment) in path.windows(2).enumerate() {
+                let (from, to) = (segment[0], segment[1]);
+                if from == to {
+                    validation.degenerate.push((line, from));
+                }
+                let cells =
+                    segment_cells(from, to).ok_or(PathError::BadSegment { line, from, to })?;
+                let last = cells.len() - 1;
+                for (j, cell) in cells.into_iter().enumerate() {
+                    // endpoints shared with the previous segment, or with the
+                    // first one when the path closes, are not retraced
+                    let shared = (k > 0 && j == 0) || (closed && k + 2 == path.len() && j == last);
+                    if !drawn.insert(cell) && !shared {
+                        retraced += 1;
                     }
-                } else if segment[0].y == segment[1].y {
-                    // horizontal
-                    let y = segment[0].y;
-                    if segment[0].x <= segment[1].x {
-                        for x in segment[0].x..=segment[1].x {
-                            s.rock.insert(Point { x, y });
-                        }
-                    } else {
-                        for x in segment[1].x..=segment[0].x {
-                            s.rock.insert(Point { x, y });
+                }
+            }
+            if retraced > 0 {
+                validation.overlapping.insert((line, line), retraced);
+            }
+            if mode == PathMode::Polygon && path.len() > 3 && closed {
+                let (min_x, max_x) = path.iter().fold((i32::MAX, i32::MIN), |(min, max), p| {
+                    (min.min(p.x), max.max(p.x))
+                });
+                let (min_y, max_y) = path.iter().fold((i32::MAX, i32::MIN), |(min, max), p| {
+                    (min.min(p.y), max.max(p.y))
+                });
+                for y in min_y..=max_y {
+                    for x in min_x..=max_x {
+                        if inside_polygon(Point { x, y }, path) {
+                            drawn.insert(Point { x, y });
                         }
                     }
+                }
+            } else if mode == PathMode::Polygon {
+                validation.unclosed.push(line);
+            }
+            for cell in drawn {
+                if let Some(owner) = owners.get(&cell) {
+                    *validation.overlapping.entry((line, *owner)).or_default() += 1;
                 } else {
-                    panic!(
-                        "Only vertical or horizontal lines. {:?} -> {:?}",
-                        segment[0], segment[1]
-                    );
+                    owners.insert(cell, line);
                 }
+                s.rock.insert(cell);
             }
         }
-        s.max_y = s.rock.iter().max_by_key(|p| p.y).unwrap().y;
-        s
+        s.max_y = s.rock.iter().map(|p| p.y).max().unwrap_or(0);
+        Ok((s, validation))
+    }
+
+    fn outside_walls(&self, p: Point) -> bool {
+        match self.floor {
+            FloorPolicy::Walls { min_x, max_x } => p.x < min_x || p.x > max_x,
+            _ => false,
+        }
     }
 
     fn accept(&self, grain: Point) -> bool {
-        let mut accept = !self.rock.contains(&grain) && !self.sand.contains(&grain);
-        if self.part == Part::Part2 {
-            accept = accept && (grain.y < self.max_y + 2)
+        let accept = !self.rock.contains(&grain) && !self.sand.contains(&grain);
+        match self.floor {
+            FloorPolicy::Abyss => accept,
+            FloorPolicy::Floor(k) => accept && grain.y < self.max_y + k,
+            FloorPolicy::Walls { .. } => {
+                accept && (self.rules.slide_off_edges || !self.outside_walls(grain))
+            }
+        }
+    }
+
+    fn is_lost(&self, grain: Point) -> bool {
+        match self.floor {
+            FloorPolicy::Floor(_) => false,
+            _ => grain.y > self.max_y || self.outside_walls(grain),
         }
-        accept
     }
 
     fn cycle(&mut self) {
         if let Some(grain) = self.falling_grain {
-            if self.accept(grain.down()) {
-                self.falling_grain = Some(grain.down());
-            } else if self.accept(grain.left()) {
-                self.falling_grain = Some(grain.left());
-            } else if self.accept(grain.right()) {
-                self.falling_grain = Some(grain.right());
-            } else {
-                self.sand.insert(grain);
-                self.falling_grain = None;
+            let next = self
+                .rules
+                .moves
+                .iter()
+                .map(|m| grain.offset(*m))
+                .find(|p| self.accept(*p));
+            match next {
+                Some(next) if self.is_lost(next) => {
+                    self.falling_grain = None;
+                    self.last_path = std::mem::take(&mut self.path);
+                    self.lost += 1;
+                    self.turns_without_settling += 1;
+                }
+                Some(next) => {
+                    self.falling_grain = Some(next);
+                    self.path.push(next);
+                }
+                None => {
+                    self.sand.insert(grain);
+                    self.falling_grain = None;
+                    self.last_path = std::mem::take(&mut self.path);
+                    self.turns_without_settling = 0;
+                }
             }
         } else {
-            self.falling_grain = Some(SOURCE);
-            self.cycle();
+            let source = self.rules.sources[self.next_source];
+            self.next_source = (self.next_source + 1) % self.rules.sources.len();
+            if self.accept(source) {
+                self.falling_grain = Some(source);
+                self.path = vec![source];
+                self.cycle();
+            } else {
+                self.turns_without_settling += 1;
+            }
         }
     }
 
+    // Once every source had a turn without any grain settling, nothing can
+    // change anymore.
     fn is_stable(&self) -> bool {
-        match self.part {
-            Part::Part1 => {
-                if let Some(grain) = self.falling_grain {
-                    !grain.will_settle(self)
-                } else {
-                    false
-                }
-            }
-            Part::Part2 => self.sand.contains(&SOURCE),
-        }
+        self.falling_grain.is_none() && self.turns_without_settling >= self.rules.sources.len()
     }
 
     fn units_of_sand_until_stable(mut self) -> usize {
@@ -187,6 +377,416 @@ impl Structure {
     }
 }
 
+#[derive(Debug, Default)]
+struct Report {
+    settled: usize,
+    lost: usize,
+    // column -> y of its topmost settled grain
+    heightmap: BTreeMap<i32, i32>,
+    // moves each settled grain made from its source, in settling order
+    steps: Vec<usize>,
+    // floor variant only: air cells below the source that no grain can reach
+    unreachable: HashSet<Point>,
+}
+
+impl Structure {
+    fn triangle(&self, source: Point) -> impl Iterator<Item = Point> + '_ {
+        let depth = match self.floor {
+            FloorPolicy::Floor(k) => self.max_y + k - source.y,
+            _ => 0,
+        };
+        (0..depth).flat_map(move |d| {
+            (-d..=d).map(move |dx| Point {
+                x: source.x + dx,
+                y: source.y + d,
+            })
+        })
+    }
+
+    fn stable_report(mut self) -> Report {
+        let mut report = Report::default();
+        while !self.is_stable() {
+            let settled = self.sand.len();
+            self.cycle();
+            if self.sand.len() != settled {
+                report.steps.push(self.last_path.len() - 1);
+            }
+        }
+        for p in &self.sand {
+            let top = report.heightmap.entry(p.x).or_insert(p.y);
+            *top = (*top).min(p.y);
+        }
+        if matches!(self.floor, FloorPolicy::Floor(_)) && self.rules == SandRules::default() {
+            report.unreachable = self
+                .triangle(SOURCE)
+                .filter(|p| !self.rock.contains(p) && !self.sand.contains(p))
+                .collect();
+        }
+        report.settled = self.sand.len();
+        report.lost = self.lost;
+        report
+    }
+
+    // Default rules with a floor: every cell of the triangle below the source
+    // fills up, except rock and the cells that rock shadows from above.
+    fn triangle_minus_shadow(&self) -> (usize, HashSet<Point>) {
+        let mut shadow = HashSet::new();
+        let mut count = 0;
+        for p in self.triangle(SOURCE) {
+            if self.rock.contains(&p) {
+                continue;
+            }
+            let shadowed = p.y > SOURCE.y
+                && [-1, 0, 1].iter().all(|dx| {
+                    let above = Point {
+                        x: p.x + dx,
+                        y: p.y - 1,
+                    };
+                    (above.x - SOURCE.x).abs() > above.y - SOURCE.y
+                        || self.rock.contains(&above)
+                        || shadow.contains(&above)
+                });
+            if shadowed {
+                shadow.insert(p);
+            } else {
+                count += 1;
+            }
+        }
+        (count, shadow)
+    }
+}
+
+struct Bitmap {
+    width: usize,
+    bits: Vec<u64>,
+}
+
+impl Bitmap {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            bits: vec![0; (width * height).div_ceil(64)],
+        }
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        let i = y * self.width + x;
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    fn set(&mut self, x: usize, y: usize) {
+        let i = y * self.width + x;
+        self.bits[i / 64] |= 1 << (i % 64);
+    }
+}
+
+struct Cave {
+    min_x: i32,
+    width: usize,
+    height: usize,
+    blocked: Bitmap,
+    max_y: i32,
+    floor: FloorPolicy,
+    moves: Vec<Point>,
+    source: Point,
+    slide_off_edges: bool,
+}
+
+impl Cave {
+    fn from(s: &Structure) -> Self {
+        assert_eq!(s.rules.sources.len(), 1, "a cave simulates a single source");
+        let source = s.rules.sources[0];
+        let (mut min_x, mut max_x) = s.rock.iter().fold((source.x, source.x), |(min, max), p| {
+            (min.min(p.x), max.max(p.x))
+        });
+        let mut max_y = s.max_y;
+        if let FloorPolicy::Floor(k) = s.floor {
+            assert!(k >= 1, "the floor lies below the lowest rock");
+            max_y += k;
+        }
+        // every cell a grain can reach from the source, so that only the
+        // abyss and the edges beyond the walls lose grains, as in `Structure`
+        let spread = s.rules.moves.iter().map(|m| m.x.abs()).max().unwrap_or(0);
+        let reach = spread * (max_y - source.y).max(0);
+        min_x = min_x.min(source.x - reach);
+        max_x = max_x.max(source.x + reach);
+        if let FloorPolicy::Walls {
+            min_x: wall_min,
+            max_x: wall_max,
+        } = s.floor
+        {
+            if !s.rules.slide_off_edges {
+                min_x = wall_min;
+                max_x = wall_max;
+            }
+        }
+        let width = (max_x - min_x + 1) as usize;
+        let height = (max_y + 1) as usize;
+        let mut blocked = Bitmap::new(width, height);
+        for p in s.rock.iter().chain(s.sand.iter()) {
+            // grains never go above their source, so rock there can be left out
+            if (min_x..=max_x).contains(&p.x) && p.y >= 0 {
+                blocked.set((p.x - min_x) as usize, p.y as usize);
+            }
+        }
+        Self {
+            min_x,
+            width,
+            height,
+            blocked,
+            max_y: s.max_y,
+            floor: s.floor,
+            moves: s.rules.moves.clone(),
+            source,
+            slide_off_edges: s.rules.slide_off_edges,
+        }
+    }
+
+    fn floor_y(&self) -> i32 {
+        self.height as i32 - 1
+    }
+
+    // `None` means the grain is lost, be it into the abyss or off the edge.
+    fn is_blocked(&self, p: Point) -> Option<bool> {
+        let outside_walls = match self.floor {
+            FloorPolicy::Walls { min_x, max_x } => p.x < min_x || p.x > max_x,
+            _ => false,
+        };
+        match self.floor {
+            FloorPolicy::Floor(_) if p.y >= self.floor_y() => Some(true),
+            FloorPolicy::Walls { .. } if outside_walls && !self.slide_off_edges => Some(true),
+            FloorPolicy::Abyss | FloorPolicy::Walls { .. } if p.y > self.max_y => None,
+            _ if self.blocked.get((p.x - self.min_x) as usize, p.y as usize) => Some(true),
+            _ if outside_walls => None,
+            _ => Some(false),
+        }
+    }
+
+    fn units_of_sand_until_stable(&mut self) -> usize {
+        let mut path = vec![self.source];
+        let mut count = 0;
+        if self.is_blocked(self.source) != Some(false) {
+            return count;
+        }
+        while let Some(&grain) = path.last() {
+            let mut settled = true;
+            for next in self.moves.iter().map(|m| grain.offset(*m)) {
+                match self.is_blocked(next) {
+                    Some(true) => continue,
+                    Some(false) => {
+                        path.push(next);
+                        settled = false;
+                        break;
+                    }
+                    None => return count,
+                }
+            }
+            if settled {
+                self.blocked
+                    .set((grain.x - self.min_x) as usize, grain.y as usize);
+                count += 1;
+                path.pop();
+            }
+        }
+        count
+    }
+
+    fn flood_fill_floor(&self) -> usize {
+        assert!(matches!(self.floor, FloorPolicy::Floor(_)), "needs a floor");
+        assert_eq!(
+            self.moves,
+            SandRules::default().moves,
+            "needs default moves"
+        );
+        let mut reachable = vec![false; self.width];
+        let source = (self.source.x - self.min_x) as usize;
+        if self.blocked.get(source, self.source.y as usize) {
+            return 0;
+        }
+        reachable[source] = true;
+        let mut count = 1;
+        for y in (self.source.y + 1) as usize..self.height - 1 {
+            let mut next = vec![false; self.width];
+            for (x, cell) in next.iter_mut().enumerate() {
+                let from_above = reachable[x.saturating_sub(1)..=(x + 1).min(self.width - 1)]
+                    .iter()
+                    .any(|r| *r);
+                if from_above && !self.blocked.get(x, y) {
+                    *cell = true;
+                    count += 1;
+                }
+            }
+            reachable = next;
+        }
+        count
+    }
+}
+
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.pixels.iter().flatten());
+        out
+    }
+}
+
+const AIR: [u8; 3] = [16, 16, 32];
+const ROCK: [u8; 3] = [128, 128, 128];
+const SAND: [u8; 3] = [230, 200, 90];
+const PATH: [u8; 3] = [80, 140, 230];
+const GRAIN: [u8; 3] = [230, 40, 40];
+
+// Bounds are computed once up front, from everywhere sand could ever settle.
+struct Renderer {
+    min: Point,
+    max: Point,
+    every: usize,
+}
+
+impl Renderer {
+    fn new(s: &Structure, every: usize) -> Self {
+        let mut min = Point {
+            x: i32::MAX,
+            y: i32::MAX,
+        };
+        let mut max = Point {
+            x: i32::MIN,
+            y: i32::MIN,
+        };
+        for p in s.rock.iter().chain(s.rules.sources.iter()) {
+            min = Point {
+                x: min.x.min(p.x),
+                y: min.y.min(p.y),
+            };
+            max = Point {
+                x: max.x.max(p.x),
+                y: max.y.max(p.y),
+            };
+        }
+        match s.floor {
+            FloorPolicy::Abyss => {}
+            FloorPolicy::Floor(k) => {
+                max.y = s.max_y + k;
+                let spread = s.rules.moves.iter().map(|m| m.x.abs()).max().unwrap_or(0);
+                for source in &s.rules.sources {
+                    let reach = spread * (max.y - source.y);
+                    min.x = min.x.min(source.x - reach);
+                    max.x = max.x.max(source.x + reach);
+                }
+            }
+            FloorPolicy::Walls { min_x, max_x } => {
+                min.x = min_x - 1;
+                max.x = max_x + 1;
+            }
+        }
+        Self {
+            min: Point {
+                x: min.x - 1,
+                y: min.y,
+            },
+            max: Point {
+                x: max.x + 1,
+                y: max.y + 1,
+            },
+            every: every.max(1),
+        }
+    }
+
+    fn width(&self) -> usize {
+        (self.max.x - self.min.x + 1) as usize
+    }
+
+    fn height(&self) -> usize {
+        (self.max.y - self.min.y + 1) as usize
+    }
+
+    fn cell(&self, s: &Structure, path: &HashSet<Point>, p: Point) -> [u8; 3] {
+        if s.falling_grain == Some(p) {
+            GRAIN
+        } else if s.rock.contains(&p) {
+            ROCK
+        } else if s.sand.contains(&p) {
+            SAND
+        } else if path.contains(&p) {
+            PATH
+        } else {
+            AIR
+        }
+    }
+
+    // Cells of the current and the last grain's paths.
+    fn path(s: &Structure) -> HashSet<Point> {
+        s.path.iter().chain(s.last_path.iter()).copied().collect()
+    }
+
+    fn image(&self, s: &Structure) -> Image {
+        let path = Self::path(s);
+        let mut pixels = Vec::with_capacity(self.width() * self.height());
+        for y in self.min.y..=self.max.y {
+            for x in self.min.x..=self.max.x {
+                pixels.push(self.cell(s, &path, Point { x, y }));
+            }
+        }
+        Image {
+            width: self.width(),
+            height: self.height(),
+            pixels,
+        }
+    }
+
+    fn ascii(&self, s: &Structure) -> String {
+        let path = Self::path(s);
+        let mut out = String::with_capacity((self.width() + 1) * self.height());
+        for y in self.min.y..=self.max.y {
+            for x in self.min.x..=self.max.x {
+                out.push(match self.cell(s, &path, Point { x, y }) {
+                    GRAIN => '+',
+                    ROCK => '#',
+                    SAND => 'o',
+                    PATH => '~',
+                    _ => '.',
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    // One frame every `every` settled grains, plus the final state.
+    fn record(&self, s: &mut Structure) -> Vec<Image> {
+        let mut frames = vec![];
+        let mut settled = s.sand.len();
+        while !s.is_stable() {
+            s.cycle();
+            if s.sand.len() != settled {
+                settled = s.sand.len();
+                if settled.is_multiple_of(self.every) {
+                    frames.push(self.image(s));
+                }
+            }
+        }
+        if !settled.is_multiple_of(self.every) || frames.is_empty() {
+            frames.push(self.image(s));
+        }
+        frames
+    }
+
+    fn write_sequence(&self, s: &mut Structure, dir: &std::path::Path) -> std::io::Result<usize> {
+        std::fs::create_dir_all(dir)?;
+        let frames = self.record(s);
+        for (i, frame) in frames.iter().enumerate() {
+            std::fs::write(dir.join(format!("frame_{i:05}.ppm")), frame.to_ppm())?;
+        }
+        Ok(frames.len())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -212,9 +812,14 @@ mod test {
     fn it_cycles() {
         let data = parse(INPUT_TEST);
         let mut structure = Structure::from(data);
+        let renderer = Renderer::new(&structure, 5);
+        let mut settled = 0;
         while !structure.is_stable() {
             structure.cycle();
-            println!("{structure}");
+            if structure.sand.len() != settled {
+                settled = structure.sand.len();
+                println!("{}", renderer.ascii(&structure));
+            }
         }
     }
 
@@ -228,7 +833,7 @@ mod test {
     fn it_cycles_part2() {
         let data = parse(INPUT_TEST);
         let mut structure = Structure::from(data);
-        structure.part = Part::Part2;
+        structure.floor = FloorPolicy::floor(2).unwrap();
         println!("{structure}");
         while !structure.is_stable() {
             structure.cycle();
@@ -239,26 +844,351 @@ mod test {
     #[test]
     fn it_counts_part2() {
         let mut structure = Structure::from(parse(INPUT_TEST));
-        structure.part = Part::Part2;
+        structure.floor = FloorPolicy::floor(2).unwrap();
         assert_eq!(structure.units_of_sand_until_stable(), 93);
     }
+
+    fn all_engines(input: &str, floor: FloorPolicy) -> Vec<usize> {
+        let mut structure = Structure::from(parse(input));
+        structure.floor = floor;
+        let mut cave = Cave::from(&structure);
+        let mut counts = vec![cave.units_of_sand_until_stable()];
+        if let FloorPolicy::Floor(_) = structure.floor {
+            counts.push(Cave::from(&structure).flood_fill_floor());
+        }
+        counts.push(structure.units_of_sand_until_stable());
+        counts
+    }
+
+    #[test]
+    fn it_cross_checks_engines() {
+        assert_eq!(all_engines(INPUT_TEST, FloorPolicy::Abyss), vec![24, 24]);
+        assert_eq!(
+            all_engines(INPUT_TEST, FloorPolicy::floor(2).unwrap()),
+            vec![93, 93, 93]
+        );
+        let part1 = all_engines(INPUT, FloorPolicy::Abyss);
+        assert!(part1.iter().all(|c| *c == part1[0]), "{part1:?}");
+        let part2 = all_engines(INPUT, FloorPolicy::floor(2).unwrap());
+        assert!(part2.iter().all(|c| *c == part2[0]), "{part2:?}");
+    }
+
+    #[test]
+    fn it_renders_frames() {
+        let mut structure = Structure::from(parse(INPUT_TEST));
+        let renderer = Renderer::new(&structure, 5);
+        for _ in 0..8 {
+            structure.cycle();
+        }
+        assert_eq!(
+            renderer.ascii(&structure),
+            r".......~....
+.......~....
+.......~....
+.......~....
+.....#.~.##.
+.....#.~.#..
+...###.~.#..
+.......~.#..
+.......+.#..
+.#########..
+............
+"
+        );
+        let frames = renderer.record(&mut structure);
+        assert_eq!(frames.len(), 5);
+        let last = frames.last().unwrap();
+        assert_eq!((last.width, last.height), (12, 11));
+        assert_eq!(last.pixels.iter().filter(|p| **p == SAND).count(), 24);
+        assert_eq!(last.pixels.iter().filter(|p| **p == GRAIN).count(), 0);
+        assert!(last.to_ppm().starts_with(b"P6\n12 11\n255\n"));
+        assert_eq!(last.to_ppm().len(), 13 + 12 * 11 * 3);
+
+        let mut structure = Structure::from(parse(INPUT_TEST));
+        structure.floor = FloorPolicy::floor(2).unwrap();
+        let renderer = Renderer::new(&structure, 10);
+        let dir = std::env::temp_dir().join(format!("day14_frames_{}", std::process::id()));
+        assert_eq!(renderer.write_sequence(&mut structure, &dir).unwrap(), 10);
+        assert!(dir.join("frame_00009.ppm").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn it_reports_stable_state() {
+        let report = Structure::from(parse(INPUT_TEST)).stable_report();
+        assert_eq!(report.settled, 24);
+        assert_eq!(report.lost, 1);
+        assert_eq!(report.steps.len(), 24);
+        assert_eq!(report.steps[0], 8);
+        assert_eq!(report.heightmap[&500], 2);
+        assert_eq!(report.heightmap[&495], 8);
+        assert!(report.unreachable.is_empty());
+
+        let mut structure = Structure::from(parse(INPUT_TEST));
+        structure.floor = FloorPolicy::floor(2).unwrap();
+        let (count, shadow) = structure.triangle_minus_shadow();
+        let report = structure.stable_report();
+        assert_eq!(report.settled, 93);
+        assert_eq!(report.lost, 0);
+        assert_eq!(*report.steps.last().unwrap(), 0);
+        assert_eq!(report.heightmap[&500], 0);
+        assert_eq!(count, report.settled);
+        assert_eq!(shadow, report.unreachable);
+        assert!(report.unreachable.contains(&Point { x: 497, y: 7 }));
+    }
+
+    #[test]
+    fn it_matches_triangle_formula() {
+        let mut structure = Structure::from(parse(INPUT));
+        structure.floor = FloorPolicy::floor(2).unwrap();
+        let (count, _) = structure.triangle_minus_shadow();
+        assert_eq!(count, Cave::from(&structure).flood_fill_floor());
+        assert_eq!(count, Cave::from(&structure).units_of_sand_until_stable());
+    }
+
+    #[test]
+    fn it_validates_paths() {
+        let (_, validation) =
+            Structure::from_paths(&parse(INPUT_TEST), PathMode::Polyline).unwrap();
+        assert!(validation.is_clean());
+
+        let paths = parse("498,4 -> 498,4 -> 498,6\n497,5 -> 499,5\n1,1\n499,5 -> 501,5");
+        let (structure, validation) = Structure::from_paths(&paths, PathMode::Polyline).unwrap();
+        assert_eq!(
+            validation.degenerate,
+            vec![(1, Point { x: 498, y: 4 }), (3, Point { x: 1, y: 1 })]
+        );
+        assert_eq!(
+            validation.overlapping,
+            BTreeMap::from([((2, 1), 1), ((4, 2), 1)])
+        );
+        assert_eq!(structure.rock.len(), 3 + 2 + 1 + 2);
+
+        let (structure, validation) =
+            Structure::from_paths(&parse("0,0 -> 4,0 -> 1,0"), PathMode::Polyline).unwrap();
+        assert_eq!(validation.overlapping, BTreeMap::from([((1, 1), 3)]));
+        assert_eq!(structure.rock.len(), 5);
+        let (_, validation) = Structure::from_paths(
+            &parse("0,0 -> 2,0 -> 2,2 -> 0,2 -> 0,0\n5,0 -> 5,3 -> 7,3 -> 5,3"),
+            PathMode::Polyline,
+        )
+        .unwrap();
+        assert_eq!(validation.overlapping, BTreeMap::from([((2, 2), 2)]));
+
+        assert_eq!(
+            Structure::from_paths(&parse("1,1 -> 2,2\n1,1 -> 3,2"), PathMode::Polyline).err(),
+            Some(PathError::BadSegment {
+                line: 2,
+                from: Point { x: 1, y: 1 },
+                to: Point { x: 3, y: 2 }
+            })
+        );
+        assert_eq!(
+            try_parse("1,1 -> 2,2\n1,1 -> 3;2").err(),
+            Some(PathError::InvalidPoint {
+                line: 2,
+                text: "3;2".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn it_draws_diagonals_and_polygons() {
+        let paths = parse("0,0 -> 3,3 -> 5,1");
+        let (structure, _) = Structure::from_paths(&paths, PathMode::Polyline).unwrap();
+        assert_eq!(structure.rock.len(), 6);
+        assert!(structure.rock.contains(&Point { x: 4, y: 2 }));
+
+        let square = parse("0,0 -> 4,0 -> 4,4 -> 0,4 -> 0,0");
+        let (outline, _) = Structure::from_paths(&square, PathMode::Polyline).unwrap();
+        assert_eq!(outline.rock.len(), 16);
+        let (filled, validation) = Structure::from_paths(&square, PathMode::Polygon).unwrap();
+        assert_eq!(filled.rock.len(), 25);
+        assert!(validation.is_clean());
+
+        let diamond = parse("2,0 -> 4,2 -> 2,4 -> 0,2 -> 2,0");
+        let (filled, validation) = Structure::from_paths(&diamond, PathMode::Polygon).unwrap();
+        assert_eq!(filled.rock.len(), 13);
+        assert!(validation.is_clean());
+
+        let open = parse("0,0 -> 4,0 -> 4,4 -> 0,4\n0,6 -> 2,6 -> 0,6\n9,9 -> 9,9");
+        let (outline, validation) = Structure::from_paths(&open, PathMode::Polygon).unwrap();
+        assert_eq!(validation.unclosed, vec![1, 2, 3]);
+        assert_eq!(outline.rock.len(), 13 + 3 + 1);
+        println!("{filled}");
+    }
+
+    #[test]
+    fn it_uses_custom_moves() {
+        let mut structure = Structure::from(parse(INPUT_TEST));
+        structure.rules = SandRules::new(
+            vec![Point { x: 0, y: 1 }, Point { x: 1, y: 1 }],
+            vec![SOURCE],
+            false,
+        )
+        .unwrap();
+        let mut cave = Cave::from(&structure);
+        assert_eq!(
+            cave.units_of_sand_until_stable(),
+            structure.units_of_sand_until_stable()
+        );
+
+        let mut structure = Structure::from(parse(INPUT_TEST));
+        structure.floor = FloorPolicy::floor(2).unwrap();
+        structure.rules = SandRules::new(
+            vec![
+                Point { x: 0, y: 1 },
+                Point { x: 2, y: 1 },
+                Point { x: -2, y: 1 },
+            ],
+            vec![SOURCE],
+            false,
+        )
+        .unwrap();
+        let mut cave = Cave::from(&structure);
+        assert_eq!(
+            cave.units_of_sand_until_stable(),
+            structure.units_of_sand_until_stable()
+        );
+
+        // grains wander past the rock's columns before settling
+        let mut structure = Structure::from(parse("9,1 -> 9,1\n5,6 -> 10,6"));
+        structure.rules = SandRules::new(
+            vec![Point { x: -1, y: 1 }, Point { x: 1, y: 1 }],
+            vec![Point { x: 10, y: 0 }],
+            false,
+        )
+        .unwrap();
+        let mut cave = Cave::from(&structure);
+        assert_eq!(cave.units_of_sand_until_stable(), 3);
+        assert_eq!(structure.units_of_sand_until_stable(), 3);
+    }
+
+    #[test]
+    fn it_has_walls() {
+        let mut structure = Structure::from(parse(INPUT_TEST));
+        structure.floor = FloorPolicy::Walls {
+            min_x: 494,
+            max_x: 503,
+        };
+        let mut cave = Cave::from(&structure);
+        let walled = cave.units_of_sand_until_stable();
+        assert_eq!(walled, structure.units_of_sand_until_stable());
+        assert!(walled > 24);
+
+        let mut structure = Structure::from(parse(INPUT_TEST));
+        structure.floor = FloorPolicy::Walls {
+            min_x: 494,
+            max_x: 503,
+        };
+        structure.rules = SandRules::new(SandRules::default().moves, vec![SOURCE], true).unwrap();
+        let mut cave = Cave::from(&structure);
+        assert_eq!(cave.units_of_sand_until_stable(), 24);
+        assert_eq!(structure.units_of_sand_until_stable(), 24);
+
+        // rock beyond a wall still blocks grains sliding off the edge
+        let mut structure = Structure::from(parse("3,3 -> 10,3"));
+        structure.floor = FloorPolicy::Walls {
+            min_x: 5,
+            max_x: 10,
+        };
+        structure.rules =
+            SandRules::new(SandRules::default().moves, vec![Point { x: 5, y: 0 }], true).unwrap();
+        let mut cave = Cave::from(&structure);
+        let sliding = cave.units_of_sand_until_stable();
+        assert_eq!(sliding, structure.units_of_sand_until_stable());
+        assert_eq!(sliding, 1);
+    }
+
+    #[test]
+    fn it_emits_from_multiple_sources() {
+        let mut structure = Structure::from(parse(INPUT_TEST));
+        structure.floor = FloorPolicy::floor(2).unwrap();
+        structure.rules = SandRules::new(
+            SandRules::default().moves,
+            vec![SOURCE, Point { x: 490, y: 0 }],
+            false,
+        )
+        .unwrap();
+        let mut turns = 0;
+        while !structure.is_stable() {
+            if structure.falling_grain.is_none() {
+                turns += 1;
+            }
+            structure.cycle();
+        }
+        println!("{structure}");
+        assert!(structure.sand.contains(&SOURCE));
+        assert!(structure.sand.contains(&Point { x: 490, y: 0 }));
+        assert!(turns >= structure.sand.len());
+
+        let mut structure = Structure::from(parse(INPUT_TEST));
+        structure.rules = SandRules::new(
+            SandRules::default().moves,
+            vec![SOURCE, Point { x: 480, y: 0 }],
+            false,
+        )
+        .unwrap();
+        assert_eq!(structure.units_of_sand_until_stable(), 24);
+    }
+
+    #[test]
+    fn it_rejects_bad_rules() {
+        let down = SandRules::default().moves;
+        assert_eq!(
+            SandRules::new(vec![], vec![SOURCE], false),
+            Err(RulesError::NoMoves)
+        );
+        assert_eq!(
+            SandRules::new(down.clone(), vec![], false),
+            Err(RulesError::NoSources)
+        );
+        assert_eq!(
+            SandRules::new(
+                vec![Point { x: 0, y: 1 }, Point { x: 1, y: 0 }],
+                vec![SOURCE],
+                false
+            ),
+            Err(RulesError::FlatMove(Point { x: 1, y: 0 }))
+        );
+        assert_eq!(
+            SandRules::new(vec![Point { x: -1, y: -1 }], vec![SOURCE], false),
+            Err(RulesError::FlatMove(Point { x: -1, y: -1 }))
+        );
+        assert_eq!(
+            SandRules::new(down, vec![Point { x: 500, y: -1 }], false),
+            Err(RulesError::SourceAbove(Point { x: 500, y: -1 }))
+        );
+        assert_eq!(FloorPolicy::floor(1), Ok(FloorPolicy::Floor(1)));
+        assert_eq!(FloorPolicy::floor(0), Err(FloorError::NotBelowRock(0)));
+        assert_eq!(FloorPolicy::floor(-20), Err(FloorError::NotBelowRock(-20)));
+    }
 }
 
-fn parse(input: &str) -> Vec<Vec<Point>> {
+fn try_parse(input: &str) -> Result<Vec<Vec<Point>>, PathError> {
     input
         .lines()
-        .map(|l| {
+        .enumerate()
+        .map(|(i, l)| {
             l.split(" -> ")
-                .map(|s| {
-                    let mut parts = s.split(',');
-                    Point {
-                        x: parts.next().unwrap().parse().unwrap(),
-                        y: parts.next().unwrap().parse().unwrap(),
-                    }
+                .map(|text| {
+                    let invalid = || PathError::InvalidPoint {
+                        line: i + 1,
+                        text: text.to_string(),
+                    };
+                    let (x, y) = text.trim().split_once(',').ok_or_else(invalid)?;
+                    Ok(Point {
+                        x: x.parse().map_err(|_| invalid())?,
+                        y: y.parse().map_err(|_| invalid())?,
+                    })
                 })
-                .collect::<Vec<_>>()
+                .collect::<Result<Vec<_>, _>>()
         })
-        .collect::<Vec<_>>()
+        .collect()
+}
+
+fn parse(input: &str) -> Vec<Vec<Point>> {
+    try_parse(input).unwrap_or_else(|e| panic!("invalid input: {e:?}"))
 }
 
 #[test]
@@ -270,6 +1200,6 @@ fn part1() {
 #[test]
 fn part2() {
     let mut structure = Structure::from(parse(INPUT));
-    structure.part = Part::Part2;
-    println!("{}", structure.units_of_sand_until_stable());
+    structure.floor = FloorPolicy::floor(2).unwrap();
+    println!("{}", Cave::from(&structure).flood_fill_floor());
 }
@@ -10,5 +10,5 @@ mod day09;
 mod day10;
 mod day11;
 mod day12;
-mod day13;
+pub mod day13;
 mod day14;